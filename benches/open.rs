@@ -0,0 +1,43 @@
+//! Compares reading a large file opened via `open()`, which maps the file into
+//! memory with the `mmap` feature, against reading it buffered.
+//!
+//! Run with `--features mmap` and with `--features cli` to compare both.
+
+use std::{
+	fs::{File, remove_file},
+	io::{self, BufReader, Read, Write},
+	time::Instant,
+};
+use mif::{Mif, First, cli::open};
+
+/// Reads `count` bytes of `bytes` once as they are and once as 32-bit words.
+fn read(path: &str, count: usize, mut bytes: Box<dyn Read>) {
+	let time = Instant::now();
+	io::copy(&mut bytes.by_ref().take(count as u64 / 2), &mut io::sink())
+		.unwrap();
+	let copied = time.elapsed();
+	let mut mif = Mif::<u32>::new(32).unwrap();
+	let time = Instant::now();
+	mif.read(&mut bytes, count / 2 / 4, First::Lsb).unwrap();
+	println!("{}: Copied {} MiB in {:?} and read {} MiB as words in {:?}",
+		path, count >> 21, copied, count >> 21, time.elapsed());
+}
+
+fn main() {
+	let path = std::env::temp_dir()
+		.join(format!("mif-bench-open-{}.bin", std::process::id()));
+	let count = 1 << 28;
+	let mut file = File::create(&path).unwrap();
+	let chunk = (0..1 << 16).map(|byte| (byte >> 6) as u8).collect::<Vec<_>>();
+	for _ in 0..count / chunk.len() {
+		file.write_all(&chunk).unwrap();
+	}
+	drop(file);
+	let name = if cfg!(feature = "mmap") { "mapped" } else { "open" };
+	let (bytes, opened) = open(&path, None).unwrap();
+	assert_eq!(opened, count);
+	read(name, count, bytes);
+	let bytes = Box::new(BufReader::new(File::open(&path).unwrap()));
+	read("buffered", count, bytes);
+	remove_file(path).unwrap();
+}
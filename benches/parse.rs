@@ -0,0 +1,34 @@
+use std::{fmt::Write, time::Instant};
+use mif::{Mif, parse::tokens};
+
+/// MIF of `depth` words each preceded by a line comment and followed by an
+/// inline block comment.
+fn commented(depth: u32) -> String {
+	let mut text = format!("-- head\n% block\ncomment %\n\
+		WIDTH=32;\nDEPTH={};\nADDRESS_RADIX=HEX;\nDATA_RADIX=HEX;\n\
+		CONTENT BEGIN\n", depth);
+	for addr in 0..depth {
+		writeln!(text, "-- Comment of word {} with some filler text", addr)
+			.unwrap();
+		writeln!(text, "\t{:X} : {:X}; % inline %", addr,
+			addr.wrapping_mul(2_654_435_761)).unwrap();
+	}
+	text.push_str("END;\n");
+	text
+}
+
+fn main() {
+	let text = commented(2_000_000);
+	let time = Instant::now();
+	let count = tokens(&text).count();
+	println!("Tokenized {} tokens with comments in {:?}",
+		count, time.elapsed());
+	let time = Instant::now();
+	let count = tokens(&text).without_comments().count();
+	println!("Tokenized {} tokens without comments in {:?}",
+		count, time.elapsed());
+	let time = Instant::now();
+	let mif = Mif::<u32>::parse(&text).unwrap();
+	println!("Parsed {} MiB into {} runs in {:?}",
+		text.len() >> 20, mif.run_count(), time.elapsed());
+}
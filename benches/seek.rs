@@ -0,0 +1,78 @@
+//! Compares joining a large memory area skipped without verification by
+//! seeking past it against reading and discarding it.
+
+use std::{
+	fs::{File, remove_file},
+	io::{self, BufReader, Read, Write},
+	path::{Path, PathBuf},
+	time::Instant,
+};
+use anyhow::Result;
+use mif::{
+	First, Style,
+	cli::{Area, Files, Input, Instr, ReadSeek, Storage, join_with},
+};
+
+/// Binaries opened as seekable or readable only and MIFs written nowhere.
+struct Bins {
+	seekable: bool,
+}
+
+impl Storage for Bins {
+	fn open(&mut self, path: &Path) -> Result<Box<dyn Read>> {
+		Ok(Box::new(BufReader::new(File::open(path)?)))
+	}
+	fn open_seekable(&mut self, path: &Path)
+	-> Result<Option<Box<dyn ReadSeek>>> {
+		Ok(if self.seekable {
+			Some(Box::new(BufReader::new(File::open(path)?)))
+		} else {
+			None
+		})
+	}
+	fn create(&mut self, _path: &Path) -> Result<Box<dyn Write>> {
+		Ok(Box::new(io::sink()))
+	}
+}
+
+/// Memory area of `depth` bytes with instruction `instr`.
+fn area(depth: usize, instr: Instr) -> Area {
+	Area {
+		first: First::Lsb,
+		width: 8,
+		depth,
+		until: None,
+		packed: false,
+		field_map: None,
+		repeat: None,
+		name: None,
+		at: None,
+		input: Input::Bin,
+		instr,
+	}
+}
+
+fn main() {
+	let path = std::env::temp_dir()
+		.join(format!("mif-bench-seek-{}.bin", std::process::id()));
+	let count = 1 << 28;
+	let mut file = File::create(&path).unwrap();
+	let chunk = vec![0xff; 1 << 16];
+	for _ in 0..count / chunk.len() {
+		file.write_all(&chunk).unwrap();
+	}
+	drop(file);
+	let mut files = Files::new();
+	files.insert(path.clone(), vec![
+		area(count - 16, Instr::Skips(Vec::new())),
+		area(16, Instr::Joins(vec![PathBuf::from("rom.mif")])),
+	]);
+	for &seekable in &[true, false] {
+		let time = Instant::now();
+		join_with(&files, &mut Bins { seekable }, &Style::default(), None)
+			.unwrap();
+		println!("Skipped {} MiB {} in {:?}", count >> 20,
+			if seekable { "by seeking" } else { "by reading" }, time.elapsed());
+	}
+	remove_file(path).unwrap();
+}
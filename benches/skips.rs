@@ -0,0 +1,32 @@
+use std::time::Instant;
+use mif::{Mif, First, cli::{Area, Input, Instr, Skip, Word, read_area}};
+
+/// Reads area of `depth` bytes cycling through `count` words to skip.
+fn skips(depth: usize, count: usize) {
+	let bytes = (0..depth).map(|addr| (addr % count) as u8).collect::<Vec<_>>();
+	let area = Area {
+		first: First::Lsb,
+		width: 8,
+		depth,
+		until: None,
+		packed: false,
+		field_map: None,
+		repeat: None,
+		name: None,
+		at: None,
+		input: Input::Bin,
+		instr: Instr::Skips((0..count as u64).map(Word::One).map(Skip::Word)
+			.collect()),
+	};
+	let mut mif = Mif::new(8).unwrap();
+	let time = Instant::now();
+	read_area(&mut mif, &mut &bytes[..], &area).unwrap();
+	println!("Skipped {} words of {} words to skip in {:?}",
+		depth, count, time.elapsed());
+}
+
+fn main() {
+	for &count in &[1, 4, 16, 17, 64, 256] {
+		skips(1 << 20, count);
+	}
+}
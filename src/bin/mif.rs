@@ -3,11 +3,30 @@
 #![forbid(unsafe_code)]
 #![forbid(missing_docs)]
 
-use std::{path::PathBuf, io::stdout};
+use std::{
+	path::{PathBuf, Path},
+	io::{self, Write, stdin, stdout, stderr, IsTerminal},
+	num::NonZeroUsize,
+	str::FromStr,
+	convert::TryFrom,
+	env::var_os,
+	fs::metadata,
+	process::exit,
+};
 use clap::{crate_version, crate_authors, Clap, AppSettings};
-use anyhow::Result;
-use mif::{First, cli::{open, dump, load, join}};
-use Mif::{Dump, Join};
+use anyhow::{Result, Error, Context, anyhow};
+use serde_json::json;
+#[cfg(feature = "trace")]
+use tracing_subscriber::{EnvFilter, fmt::format::FmtSpan};
+use mif::{
+	First, Format, Ecc, Style, HexCase, KeywordCase, Dialect,
+	cli::{
+		Order, Width, Origin, Target, Source, open, open_all, pad_to, dump,
+		info, load, load_from, sort_areas, join, verify, save, create, jobs,
+		convert, expand,
+	},
+};
+use Command::{Dump, Info, Join, Convert};
 
 /// Memory Initialization File.
 #[derive(Clap)]
@@ -20,9 +39,131 @@ use Mif::{Dump, Join};
 	global_setting = AppSettings::UnifiedHelpMessage,
 	global_setting = AppSettings::ArgRequiredElseHelp,
 )]
-enum Mif {
+struct Mif {
+	/// Error output: human or json.
+	#[clap(long = "format-errors", value_name = "human|json")]
+	#[clap(default_value = "human")]
+	errors: Errors,
+	/// Prints no warnings, errors only.
+	#[clap(short = "q", long = "quiet", global = true)]
+	quiet: bool,
+	/// Prints progress and summary to standard error.
+	#[clap(short = "v", long = "verbose", global = true)]
+	#[clap(conflicts_with = "quiet")]
+	verbose: bool,
+	#[clap(subcommand)]
+	command: Command,
+}
+
+#[derive(Clap)]
+enum Command {
 	/// Dumps binary as MIF.
 	Dump {
+		/// Input files concatenated or standard input (-).
+		#[clap(default_value = "-")]
+		inputs: Vec<PathBuf>,
+		/// Word width in bits from 1 to 128 or auto guessing 8, 16, 24, or 32.
+		#[clap(short = "w", long = "width", value_name = "bits|auto")]
+		#[clap(default_value = "16")]
+		width: Width,
+		/// LSB/MSB first (little/big-endian), native, or auto.
+		#[clap(short = "f", long = "first", value_name = "lsb|msb|native|auto")]
+		#[clap(default_value = "lsb")]
+		first: Order,
+		/// Bytes per group read LSB/MSB first of mixed-endian words, say 2 for
+		/// 32-bit words of two 16-bit halves.
+		#[clap(long = "byte-group", value_name = "bytes")]
+		group: Option<NonZeroUsize>,
+		/// LSB/MSB first (little/big-endian) or native of byte groups.
+		#[clap(long = "group-first", value_name = "lsb|msb|native")]
+		#[clap(default_value = "msb")]
+		group_first: First,
+		/// Output format: mif, ihex, srec, coe, readmemh, vhdl, or c.
+		#[clap(short = "F", long = "format", value_name = "format")]
+		#[clap(default_value = "mif")]
+		format: Format,
+		/// Data bytes per record from 1 to 255 of Intel HEX or S-record
+		/// format [default: 16].
+		#[clap(long = "record-bytes", value_name = "bytes")]
+		#[clap(parse(try_from_str = record))]
+		record: Option<usize>,
+		/// Check bits concatenated to words: even, odd, or hamming-<n>-<k>.
+		#[clap(long = "ecc", value_name = "scheme")]
+		ecc: Option<Ecc>,
+		/// Lowercase hexadecimal digits except of Intel HEX and S-record.
+		#[clap(short = "l", long = "lowercase")]
+		lower: bool,
+		/// Lowercase keywords in MIF as in width instead of WIDTH.
+		#[clap(long = "lowercase-keywords")]
+		lower_keys: bool,
+		/// No blank lines between header sections in MIF.
+		#[clap(short = "c", long = "compact")]
+		compact: bool,
+		/// Stamps version of mif on top of MIF.
+		#[clap(long = "stamp")]
+		stamp: bool,
+		/// Comments after WIDTH, DEPTH, and radix header of MIF.
+		#[clap(long = "header-first")]
+		header_first: bool,
+		/// Writes every address on its own line instead of as ranges.
+		#[clap(long = "no-runs")]
+		single: bool,
+		/// Minimum words of runs written as ranges of MIF or VHDL format
+		/// [default: 2] or marked by address of readmemh format, shorter ones
+		/// on their own lines.
+		#[clap(long = "min-run", value_name = "words")]
+		#[clap(conflicts_with = "single")]
+		min_run: Option<NonZeroUsize>,
+		/// Aligns colons and words of all lines in MIF.
+		#[clap(long = "align-columns")]
+		align: bool,
+		/// MIF dialect: quartus, generic, or gowin.
+		#[clap(long = "dialect", value_name = "dialect")]
+		#[clap(default_value = "quartus")]
+		dialect: Dialect,
+		/// Fixed count of address digits in MIF [default: fewest required].
+		#[clap(long = "address-digits", value_name = "digits")]
+		digits: Option<usize>,
+		/// Comments LSB/MSB first as FIRST=lsb or FIRST=msb in MIF.
+		#[clap(long = "comment-first")]
+		comment: bool,
+		/// Signed origin added to addresses as written in MIF, say -0x10.
+		#[clap(long = "origin", value_name = "address")]
+		#[clap(allow_hyphen_values = true, parse(try_from_str = origin))]
+		#[clap(default_value = "0")]
+		origin: isize,
+		/// Address stride per word in MIF, say 4 to address 32-bit words by
+		/// bytes [default: 1].
+		#[clap(long = "addr-step", value_name = "step")]
+		step: Option<NonZeroUsize>,
+		/// Mask ANDed with words as written in MIF, say 0xff00.
+		#[clap(long = "and-mask", value_name = "word")]
+		#[clap(parse(try_from_str = word))]
+		and_mask: Option<u128>,
+		/// Mask ORed with words as written in MIF after the AND mask.
+		#[clap(long = "or-mask", value_name = "word")]
+		#[clap(parse(try_from_str = word))]
+		or_mask: Option<u128>,
+		/// Byte offset into input.
+		#[clap(long = "offset", alias = "skip-bytes", value_name = "bytes")]
+		#[clap(default_value = "0")]
+		offset: usize,
+		/// Count of input bytes from offset [default: to end of input].
+		#[clap(long = "length", alias = "count-bytes", value_name = "bytes")]
+		length: Option<usize>,
+		/// Maximum count of input bytes.
+		#[clap(long = "max-input-bytes", value_name = "bytes")]
+		limit: Option<usize>,
+		/// Pads input to next word with byte instead of erroring, say 0xff.
+		#[clap(long = "pad-to", value_name = "byte")]
+		#[clap(parse(try_from_str = byte))]
+		pad: Option<u8>,
+		/// Comments CRC-32 of every block of words in MIF.
+		#[clap(long = "block-crc", value_name = "words")]
+		block: Option<NonZeroUsize>,
+	},
+	/// Prints binary's MIF information.
+	Info {
 		/// Input file or standard input (-).
 		#[clap(default_value = "-")]
 		input: PathBuf,
@@ -30,10 +171,14 @@ enum Mif {
 		#[clap(short = "w", long = "width", value_name = "bits")]
 		#[clap(default_value = "16")]
 		width: usize,
-		/// LSB/MSB first (little/big-endian).
-		#[clap(short = "f", long = "first", value_name = "lsb|msb")]
+		/// LSB/MSB first (little/big-endian) or native.
+		#[clap(short = "f", long = "first", value_name = "lsb|msb|native")]
 		#[clap(default_value = "lsb")]
 		first: First,
+		/// Counts addresses holding word, say 0xff, as word-count and
+		/// word-ratio.
+		#[clap(long = "count", value_name = "word", parse(try_from_str = word))]
+		word: Option<u128>,
 	},
 	/// Joins binaries' memory areas to MIFs.
 	Join {
@@ -43,25 +188,388 @@ enum Mif {
 		/// Input directory [default: .].
 		#[clap(short = "i", long = "bins", value_name = "path")]
 		bins: Option<PathBuf>, // `default_value = ""` broken for non-pos opts.
+		/// Base directory of relative input and output directories and of
+		/// piped TOML's includes [default: .].
+		#[clap(short = "t", long = "toml-dir", value_name = "path")]
+		base: Option<PathBuf>, // `default_value = ""` broken for non-pos opts.
 		/// Output directory [default: .].
 		#[clap(short = "o", long = "mifs", value_name = "path")]
 		mifs: Option<PathBuf>, // `default_value = ""` broken for non-pos opts.
 		/// No comments in MIFs.
 		#[clap(short = "n", long = "no-comments")]
 		nocs: bool,
+		/// Comments runs with their binaries and skipped areas in MIFs.
+		#[clap(long = "verbose-comments", conflicts_with = "nocs")]
+		vers: bool,
+		/// Comments areas inline where they begin instead of on top of MIFs.
+		#[clap(long = "inline-comments", conflicts_with = "nocs")]
+		inline: bool,
+		/// Joins binaries sorted by path instead of in given order.
+		#[clap(short = "s", long = "sort")]
+		sort: bool,
+		/// Joins areas of binaries sorted by offset instead of in given order.
+		#[clap(long = "sort-areas")]
+		sort_areas: bool,
+		/// Lowercase hexadecimal digits in MIFs.
+		#[clap(short = "l", long = "lowercase")]
+		lower: bool,
+		/// Lowercase keywords in MIFs as in width instead of WIDTH.
+		#[clap(long = "lowercase-keywords")]
+		lower_keys: bool,
+		/// No blank lines between header sections in MIFs.
+		#[clap(short = "c", long = "compact")]
+		compact: bool,
+		/// Stamps version of mif on top of MIFs.
+		#[clap(long = "stamp")]
+		stamp: bool,
+		/// Comments after WIDTH, DEPTH, and radix header of MIFs.
+		#[clap(long = "header-first")]
+		header_first: bool,
+		/// Aligns colons and words of all lines in MIFs.
+		#[clap(long = "align-columns")]
+		align: bool,
+		/// MIF dialect: quartus, generic, or gowin.
+		#[clap(long = "dialect", value_name = "dialect")]
+		#[clap(default_value = "quartus")]
+		dialect: Dialect,
+		/// Fixed count of address digits in MIFs [default: fewest required].
+		#[clap(long = "address-digits", value_name = "digits")]
+		digits: Option<usize>,
+		/// Address stride per word in MIFs, say 4 to address 32-bit words by
+		/// bytes [default: 1].
+		#[clap(long = "addr-step", value_name = "step")]
+		step: Option<NonZeroUsize>,
+		/// Data bytes per record from 1 to 255 of Intel HEX or S-record MIFs
+		/// [default: 16].
+		#[clap(long = "record-bytes", value_name = "bytes")]
+		#[clap(parse(try_from_str = record))]
+		record: Option<usize>,
+		/// Maximum depth of MIFs in words.
+		#[clap(short = "d", long = "max-depth", value_name = "words")]
+		limit: Option<usize>,
+		/// Manifest TOML file or standard output (-).
+		#[clap(short = "m", long = "manifest", value_name = "path")]
+		list: Option<PathBuf>,
+		/// Maximum count of threads [default: count of CPUs].
+		#[clap(short = "j", long = "jobs", value_name = "count")]
+		jobs: Option<NonZeroUsize>,
+	},
+	/// Converts between formats.
+	Convert {
+		/// Input file or standard input (-).
+		#[clap(default_value = "-")]
+		input: PathBuf,
+		/// Output file or standard output (-).
+		#[clap(default_value = "-")]
+		output: PathBuf,
+		/// Input format: bin, mif, ihex, or srec.
+		#[clap(long = "from", value_name = "format")]
+		#[clap(default_value = "bin")]
+		from: Origin,
+		/// Output format: bin, mif, ihex, srec, coe, readmemh, vhdl, or c.
+		#[clap(long = "to", value_name = "format")]
+		#[clap(default_value = "mif")]
+		to: Target,
+		/// Word width in bits from 1 to 128 of non-MIF input.
+		#[clap(short = "w", long = "width", value_name = "bits")]
+		width: Option<usize>,
+		/// LSB/MSB first (little/big-endian) or native of byte-oriented
+		/// formats [default: as commented in MIF input or lsb].
+		#[clap(short = "f", long = "first", value_name = "lsb|msb|native")]
+		first: Option<First>,
+		/// Rejects non-canonical MIF input.
+		#[clap(long = "strict")]
+		strict: bool,
+		/// Lowercase hexadecimal digits except of Intel HEX and S-record
+		/// output.
+		#[clap(short = "l", long = "lowercase")]
+		lower: bool,
+		/// No blank lines between header sections in MIF output.
+		#[clap(short = "c", long = "compact")]
+		compact: bool,
+		/// Stamps version of mif on top of MIF output.
+		#[clap(long = "stamp")]
+		stamp: bool,
+		/// Data bytes per record from 1 to 255 of Intel HEX or S-record output
+		/// [default: 16].
+		#[clap(long = "record-bytes", value_name = "bytes")]
+		#[clap(parse(try_from_str = record))]
+		record: Option<usize>,
+		/// Minimum words of runs written as ranges of MIF or VHDL output
+		/// [default: 2] or marked by address of readmemh output, shorter ones
+		/// on their own lines.
+		#[clap(long = "min-run", value_name = "words")]
+		min_run: Option<NonZeroUsize>,
 	},
 }
 
-fn main() -> Result<()> {
-	match Mif::parse() {
-		Dump { input, width, first } => {
-			let (mut bytes, count) = open(&input)?;
-			dump(&mut stdout(), &mut bytes, count, width, first)
+fn case(lower: bool) -> HexCase {
+	if lower { HexCase::Lower } else { HexCase::Upper }
+}
+
+fn keyword_case(lower: bool) -> KeywordCase {
+	if lower { KeywordCase::Lower } else { KeywordCase::Upper }
+}
+
+fn version(stamp: bool) -> Option<&'static str> {
+	if stamp { Some(crate_version!()) } else { None }
+}
+
+/// Parses decimal or with `0x`, `0o`, or `0b` prefixed word.
+fn word(from: &str) -> Result<u128> {
+	let (radix, digits) = match from.get(..2) {
+		Some("0x") | Some("0X") => (16, &from[2..]),
+		Some("0o") | Some("0O") => (8, &from[2..]),
+		Some("0b") | Some("0B") => (2, &from[2..]),
+		_ => (10, from),
+	};
+	u128::from_str_radix(digits, radix)
+		.map_err(|_| anyhow!("Invalid word `{}`", from))
+}
+
+/// Parses byte, see `word()`.
+fn byte(from: &str) -> Result<u8> {
+	u8::try_from(word(from)?).map_err(|_| anyhow!("Invalid byte `{}`", from))
+}
+
+/// Parses data bytes per record from 1 to 255.
+fn record(from: &str) -> Result<usize> {
+	from.parse().ok().filter(|record| (1..=255).contains(record))
+		.ok_or_else(|| anyhow!("Invalid record bytes `{}`", from))
+}
+
+/// Parses signed address, see `word()`.
+fn origin(from: &str) -> Result<isize> {
+	let (sign, addr) = match from.strip_prefix('-') {
+		Some(addr) => (-1, addr),
+		None => (1, from),
+	};
+	isize::try_from(word(addr)?).map(|addr| sign * addr)
+		.map_err(|_| anyhow!("Invalid address `{}`", from))
+}
+
+/// Error output.
+#[derive(Copy, Clone)]
+enum Errors {
+	/// Human-readable error colored if standard error is a terminal.
+	Human,
+	/// Machine-readable error as one JSON object per line.
+	Json,
+}
+
+impl FromStr for Errors {
+	type Err = Error;
+
+	fn from_str(from: &str) -> Result<Self> {
+		match from {
+			"human" => Ok(Errors::Human),
+			"json" => Ok(Errors::Json),
+			_ => Err(anyhow!("Valid values are `human` and `json`")),
+		}
+	}
+}
+
+impl Command {
+	/// Input file of subcommand.
+	fn input(&self) -> &Path {
+		match self {
+			Dump { inputs, .. } =>
+				inputs.first().map_or(Path::new("-"), PathBuf::as_path),
+			Info { input, .. } | Convert { input, .. } => input,
+			Join { toml, .. } => toml,
+		}
+	}
+}
+
+/// Prints `err` of `input` file as `errors`.
+fn report(err: &Error, input: &Path, errors: Errors) {
+	match errors {
+		Errors::Human => {
+			let color = stderr().is_terminal() && var_os("NO_COLOR").is_none();
+			let error =
+				if color { "\x1b[1;31mError:\x1b[0m" } else { "Error:" };
+			eprintln!("{} {:?}", error, err);
+		},
+		Errors::Json => {
+			let mif = err.chain()
+				.find_map(|err| err.downcast_ref::<mif::Error>());
+			let kind = match mif {
+				Some(mif) => format!("{:?}", mif).split('(').next()
+					.unwrap_or_default().to_string(),
+				None if err.chain().any(|err| err.is::<io::Error>()) =>
+					"IoError".into(),
+				None => "Other".into(),
+			};
+			let address = mif.and_then(|mif| match *mif {
+				mif::Error::ValueOutOfWidth(addr, _) |
+				mif::Error::AddressOutOfRange(addr) |
+				mif::Error::OutOfOrder(addr) |
+				mif::Error::DuplicateAddress(addr) |
+				mif::Error::NonCoalesced(addr) |
+				mif::Error::EmbeddedTerminator(addr) => Some(addr),
+				_ => None,
+			});
+			eprintln!("{}", json!({
+				"kind": kind,
+				"file": input,
+				"address": address,
+				"message": err.to_string(),
+				"causes": err.chain().skip(1).map(ToString::to_string)
+					.collect::<Vec<_>>(),
+			}));
+		},
+	}
+}
+
+fn main() {
+	#[cfg(feature = "trace")]
+	tracing_subscriber::fmt()
+		.with_env_filter(EnvFilter::from_default_env())
+		.with_span_events(FmtSpan::CLOSE)
+		.with_writer(stderr)
+		.with_ansi(stderr().is_terminal() && var_os("NO_COLOR").is_none())
+		.init();
+	let Mif { errors, quiet, verbose, command } = Mif::parse();
+	let input = command.input().to_path_buf();
+	if let Err(err) = run(command, quiet, verbose) {
+		report(&err, &input, errors);
+		exit(1);
+	}
+}
+
+fn run(command: Command, quiet: bool, verbose: bool) -> Result<()> {
+	match command {
+		Dump {
+			inputs, width, first, group, group_first, format, record, ecc,
+			lower, lower_keys, compact, stamp, header_first, single, min_run,
+			align, dialect, digits, comment, origin, step, and_mask, or_mask,
+			offset, length, limit, pad, block,
+		} => {
+			let (mut bytes, mut count) =
+				open_all(&inputs, offset, length, limit)?;
+			if let Some(fill) = pad {
+				let width = match width {
+					Width::Given(width) => width,
+					Width::Guess =>
+						return Err(anyhow!("Padding requires given width")),
+				};
+				(bytes, count) = pad_to(bytes, count, width, fill);
+			}
+			let style = Style {
+				case: case(lower),
+				keywords: keyword_case(lower_keys),
+				blocks: block,
+				compact,
+				version: version(stamp),
+				header_first,
+				align,
+				single,
+				min_run: min_run.map(NonZeroUsize::get),
+				dialect,
+				digits,
+				first: if comment { Some(First::default()) } else { None },
+				origin,
+				addr_step: step.map(NonZeroUsize::get),
+				record_bytes: record,
+				and_mask,
+				or_mask,
+				..Style::default()
+			};
+			let group = group.map(|group| (group.get(), group_first));
+			let mif = dump(&mut stdout(), &mut bytes, count, width, first,
+				group, format, ecc, &style)?;
+			if width == Width::Guess && !quiet {
+				eprintln!("Guessed width of {} bits, which may be wrong",
+					mif.width());
+			}
+			if verbose {
+				eprintln!("Dumped {} B as {} words of {} bits in {} runs",
+					count, mif.depth(), mif.width(), mif.words().len());
+			}
+			Ok(())
+		},
+		Info { input, width, first, word } => {
+			let (mut bytes, count) = open(&input, None)?;
+			info(&mut stdout(), &mut bytes, count, width, first, word)
+		},
+		Join {
+			toml, bins, base, mifs, nocs, vers, inline, sort,
+			sort_areas: by_offset, lower, lower_keys, compact, stamp,
+			header_first, align, dialect, digits, step, record, limit, list,
+			jobs: count,
+		} => {
+			let warning = count.map(|count| jobs(count.get())).transpose()?;
+			if let (Some(warning), false) = (warning.flatten(), quiet) {
+				eprintln!("Warning: {}", warning);
+			}
+			let base = base.unwrap_or_default();
+			let bins = base.join(bins.unwrap_or_default());
+			let mifs = base.join(mifs.unwrap_or_default());
+			let style = Style {
+				areas: !nocs,
+				inline,
+				sources: vers,
+				case: case(lower),
+				keywords: keyword_case(lower_keys),
+				compact,
+				version: version(stamp),
+				header_first,
+				align,
+				dialect,
+				digits,
+				addr_step: step.map(NonZeroUsize::get),
+				record_bytes: record,
+				..Style::default()
+			};
+			let (mut files, expects) = if toml == Path::new("-") {
+				load_from(&mut stdin(), &base)?
+			} else {
+				load(&toml)?
+			};
+			if verbose {
+				eprintln!("Loaded {} areas of {} binaries from `{}`",
+					files.values().map(Vec::len).sum::<usize>(), files.len(),
+					toml.display());
+			}
+			if sort {
+				files.sort_keys();
+			}
+			if by_offset {
+				sort_areas(&mut files)?;
+			}
+			let manifest = join(&files, (&bins, &mifs), &style, limit)?;
+			verify(&manifest, &expects)?;
+			if verbose {
+				for (mif_path, joined) in &manifest {
+					let path = expand(&mifs)?.join(expand(mif_path)?);
+					eprintln!("Joined {} areas to `{}` of {} words in {} B",
+						joined.areas.len(), mif_path.display(), joined.depth,
+						metadata(&path)?.len());
+					for Source { addr, path } in &joined.areas {
+						eprintln!("\t{:#x}: {}", addr, path.display());
+					}
+				}
+			}
+			list.map_or(Ok(()), |list| save(&manifest, &list))
 		},
-		Join { toml, bins, mifs, nocs } => {
-			let bins = bins.unwrap_or_default();
-			let mifs = mifs.unwrap_or_default();
-			join(&load(&toml)?, (&bins, &mifs), !nocs)
+		Convert {
+			input, output, from, to, width, first, strict, lower, compact,
+			stamp, record, min_run,
+		} => {
+			let (mut bytes, _count) = open(&input, None)?;
+			let style = Style {
+				case: case(lower),
+				compact,
+				version: version(stamp),
+				record_bytes: record,
+				min_run: min_run.map(NonZeroUsize::get),
+				..Style::default()
+			};
+			let mut lines = create(&output)?;
+			convert(&mut lines, &mut bytes, from, to, width, first, strict,
+				&style)?;
+			lines.flush()
+				.with_context(|| format!("Cannot write `{}`", output.display()))
 		},
 	}
 }
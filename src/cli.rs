@@ -1,140 +1,1308 @@
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "mmap"), forbid(unsafe_code))]
+#![cfg_attr(feature = "mmap", deny(unsafe_code))]
 #![forbid(missing_docs)]
 
 use std::{
+	env::var,
 	convert::TryInto,
 	path::{PathBuf, Path},
-	fs::{OpenOptions, metadata},
-	io::{Cursor, BufReader, Read, stdin, BufWriter, Write},
+	collections::BTreeSet,
+	fs::{File, OpenOptions, metadata},
+	str::FromStr,
+	iter::once,
+	io::{
+		self, Cursor, BufReader, Read, stdin, BufWriter, Write, stdout, Seek,
+		SeekFrom, copy, sink,
+	},
+	fmt::UpperHex,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize, Serializer};
+use num_traits::{CheckedShl, FromPrimitive, PrimInt, Unsigned, WrappingSub};
 use indexmap::IndexMap;
-use anyhow::{Result, Context, ensure};
+use toml::Value;
+use anyhow::{Result, Context, ensure, anyhow};
+#[cfg(feature = "mmap")]
+use memmap2::Mmap;
+#[cfg(feature = "flate")]
+use {std::io::BufRead, flate2::bufread::MultiGzDecoder};
+#[cfg(feature = "trace")]
+use tracing::{info_span, info};
+#[cfg(feature = "parallel")]
+use rayon::{ThreadPoolBuilder, iter::{IntoParallelIterator, ParallelIterator}};
 use Instr::{Skips, Joins};
-use crate::{Mif, First, default_width};
+use crate::{
+	Mif, First, Format, Ecc, Style, HexCase, Hex, crc32, default_width, parse,
+};
+
+/// Environment variable of home directory.
+#[cfg(windows)]
+const HOME: &str = "USERPROFILE";
+/// Environment variable of home directory.
+#[cfg(not(windows))]
+const HOME: &str = "HOME";
+
+/// Expands leading `~` and environment variables as in `$VAR` or `${VAR}`.
+///
+/// The `~` is expanded to the home directory given by `HOME` or on Windows by
+/// `USERPROFILE` if it is followed by a path separator or nothing. Undefined
+/// environment variables are an error. Non-UTF-8 paths are returned as is.
+pub fn expand(path: &dyn AsRef<Path>) -> Result<PathBuf> {
+	let path = path.as_ref();
+	let mut rest = match path.to_str() {
+		Some(rest) => rest,
+		None => return Ok(path.to_path_buf()),
+	};
+	let mut string = String::new();
+	if rest == "~" || rest.starts_with("~/")
+	|| (cfg!(windows) && rest.starts_with("~\\")) {
+		string += &var(HOME).with_context(|| format!(
+			"Cannot expand `~` in `{}`", path.display()))?;
+		rest = &rest[1..];
+	}
+	while let Some(index) = rest.find('$') {
+		string += &rest[..index];
+		rest = &rest[index + 1..];
+		let (name, tail) = if let Some(braced) = rest.strip_prefix('{') {
+			let end = braced.find('}').with_context(|| format!(
+				"Unclosed `${{` in `{}`", path.display()))?;
+			(&braced[..end], &braced[end + 1..])
+		} else {
+			let end = rest
+				.find(|char: char| !char.is_ascii_alphanumeric() && char != '_')
+				.unwrap_or(rest.len());
+			(&rest[..end], &rest[end..])
+		};
+		ensure!(!name.is_empty(),
+			"Missing variable name after `$` in `{}`", path.display());
+		string += &var(name).with_context(|| format!(
+			"Cannot expand `${}` in `{}`", name, path.display()))?;
+		rest = tail;
+	}
+	string += rest;
+	Ok(string.into())
+}
 
 /// Opens file or standard input `"-"` as buffered bytes reader of known count.
 ///
-///   * For a file, the count is determined by `metadata()`.
-///   * For standard input, the bytes are completely read in and counted.
-pub fn open(input: &dyn AsRef<Path>) -> Result<(Box<dyn Read>, usize)> {
+/// Equals `open_at()` at offset zero without length.
+pub fn open(input: &dyn AsRef<Path>, limit: Option<usize>)
+-> Result<(Box<dyn Read>, usize)> {
+	open_at(input, 0, None, limit)
+}
+
+/// Opens file or standard input `"-"` as buffered bytes reader of known count
+/// starting at byte `offset` and ending after optional `length` in bytes.
+///
+///   * For a file, the count is determined by `metadata()` and the `offset`
+///     is sought. With the `mmap` feature, the file is mapped into memory
+///     instead of being buffered.
+///   * For standard input, the bytes up to the `offset` are discarded and the
+///     remaining bytes are completely read in and counted.
+///   * With the `flate` feature, a gzip-compressed file, detected by its `.gz`
+///     extension or its magic bytes, is decompressed and read in as standard
+///     input is, counting its decompressed bytes.
+///
+/// Errors if the input ends before the `offset` or before the `length` or if
+/// the count exceeds the optional `limit` in bytes, in which case standard
+/// input is read in no further than one byte beyond the `limit`.
+///
+/// The path of a file is expanded, see `expand()`.
+pub fn open_at(
+	input: &dyn AsRef<Path>,
+	offset: usize,
+	length: Option<usize>,
+	limit: Option<usize>,
+) -> Result<(Box<dyn Read>, usize)> {
 	let input = input.as_ref();
-	Ok(if input == Path::new("-") {
-		let mut bytes = Vec::new();
-		stdin().read_to_end(&mut bytes).context("Cannot read standard input")?;
+	let (bytes, count): (Box<dyn Read>, usize) = if input == Path::new("-") {
+		let (bytes, skipped) = read_in(&mut stdin(), offset, length, limit)
+			.context("Cannot read standard input")?;
+		ensure!(skipped == offset,
+			"Standard input ends at byte {} before offset", skipped);
+		let count = bytes.len();
+		(Box::new(Cursor::new(bytes)), count)
+	} else if let Some(mut bytes) = gunzip(&expand(&input)?)? {
+		let (bytes, skipped) = read_in(&mut bytes, offset, length, limit)
+			.with_context(|| format!(
+				"Cannot decompress `{}`", input.display()))?;
+		ensure!(skipped == offset, "`{}` ends at byte {} before offset",
+			input.display(), skipped);
 		let count = bytes.len();
 		(Box::new(Cursor::new(bytes)), count)
 	} else {
-		let (bytes, count) = OpenOptions::new().read(true).open(&input)
-			.and_then(|bytes| metadata(&input)
-				.map(|stats| (BufReader::new(bytes), stats.len())))
+		let input = &expand(&input)?;
+		let (mut bytes, count) = OpenOptions::new().read(true).open(input)
+			.and_then(|bytes| metadata(input)
+				.and_then(|stats| Ok((buffer(bytes)?, stats.len()))))
 			.with_context(|| format!("Cannot open `{}`", input.display()))?;
-		(Box::new(bytes), count.try_into().context("Address space exhausted")?)
+		let count: usize = count.try_into().context("Address space exhausted")?;
+		ensure!(offset <= count, "`{}` ends at byte {} before offset",
+			input.display(), count);
+		bytes.seek(SeekFrom::Start(offset as u64))
+			.with_context(|| format!("Cannot seek `{}`", input.display()))?;
+		(Box::new(bytes), count - offset)
+	};
+	let count = match length {
+		Some(length) => {
+			ensure!(length <= count, "Input of `{}` ends {} B before length",
+				input.display(), length - count);
+			length
+		},
+		None => count,
+	};
+	if let Some(limit) = limit {
+		ensure!(count <= limit, "Input of `{}` exceeds {} B",
+			input.display(), limit);
+	}
+	Ok((Box::new(bytes.take(count as u64)), count))
+}
+
+/// Opens files or standard input `"-"` as one concatenated bytes reader of
+/// known count starting at byte `offset` and ending after optional `length`
+/// in bytes of the concatenation.
+///
+/// Equals `open_at()` for one input. Errors if the concatenation ends before
+/// the `offset` or before the `length` or if the count exceeds the optional
+/// `limit` in bytes.
+pub fn open_all(
+	inputs: &[PathBuf],
+	offset: usize,
+	length: Option<usize>,
+	limit: Option<usize>,
+) -> Result<(Box<dyn Read>, usize)> {
+	if let [input] = inputs {
+		return open_at(input, offset, length, limit);
+	}
+	let mut bytes: Box<dyn Read> = Box::new(io::empty());
+	let mut count = 0usize;
+	for input in inputs {
+		let max = limit.map(|limit| limit.saturating_add(offset));
+		let (input, input_count) = open(input, max)?;
+		count = count.checked_add(input_count)
+			.context("Address space exhausted")?;
+		bytes = Box::new(bytes.chain(input));
+	}
+	ensure!(offset <= count, "Inputs end at byte {} before offset", count);
+	copy(&mut bytes.by_ref().take(offset as u64), &mut sink())
+		.context("Cannot read inputs")?;
+	let count = match length {
+		Some(length) => {
+			ensure!(length <= count - offset,
+				"Inputs end {} B before length", length - (count - offset));
+			length
+		},
+		None => count - offset,
+	};
+	if let Some(limit) = limit {
+		ensure!(count <= limit, "Inputs exceed {} B", limit);
+	}
+	Ok((Box::new(bytes.take(count as u64)), count))
+}
+
+/// Pads `bytes` of `count` with `fill` bytes up to the next boundary of words
+/// of `width` in bits, completing a partial final word.
+///
+/// Opt-in as it changes the content, see `read_dump()`.
+pub fn pad_to(bytes: Box<dyn Read>, count: usize, width: usize, fill: u8)
+-> (Box<dyn Read>, usize) {
+	let align = width.div_ceil(8);
+	let pad = count.next_multiple_of(align) - count;
+	(Box::new(bytes.chain(io::repeat(fill).take(pad as u64))), count + pad)
+}
+
+/// Reads in `bytes` completely after discarding the bytes up to `offset` but
+/// no further than one byte beyond the `length` or the `limit`, whichever is
+/// less, returning the bytes read in and the count of bytes discarded.
+fn read_in(
+	bytes: &mut dyn Read,
+	offset: usize,
+	length: Option<usize>,
+	limit: Option<usize>,
+) -> io::Result<(Vec<u8>, usize)> {
+	let skipped = copy(&mut bytes.take(offset as u64), &mut sink())?;
+	let mut read = Vec::new();
+	let max = length.into_iter().chain(limit).min()
+		.map_or(u64::MAX, |max| (max as u64).saturating_add(1));
+	bytes.take(max).read_to_end(&mut read)?;
+	Ok((read, skipped as usize))
+}
+
+/// Opens `file` as decompressing reader if it is gzip-compressed.
+///
+/// Detects gzip by the `.gz` extension in any case or by the magic bytes.
+#[cfg(feature = "flate")]
+fn gunzip(file: &Path) -> Result<Option<Box<dyn Read>>> {
+	let gz = file.extension()
+		.is_some_and(|extension| extension.eq_ignore_ascii_case("gz"));
+	let mut bytes = OpenOptions::new().read(true).open(file)
+		.map(BufReader::new)
+		.with_context(|| format!("Cannot open `{}`", file.display()))?;
+	let magic = bytes.fill_buf()
+		.with_context(|| format!("Cannot read `{}`", file.display()))?
+		.starts_with(&[0x1f, 0x8b]);
+	Ok(if gz || magic {
+		Some(Box::new(MultiGzDecoder::new(bytes)))
+	} else {
+		None
 	})
 }
 
+/// Never gzip-compressed without `flate` feature.
+#[cfg(not(feature = "flate"))]
+fn gunzip(_file: &Path) -> Result<Option<Box<dyn Read>>> {
+	Ok(None)
+}
+
+/// Maps `file` into memory.
+#[cfg(feature = "mmap")]
+#[allow(unsafe_code)]
+fn buffer(file: File) -> io::Result<Cursor<Mmap>> {
+	// Safety: Undefined behavior if the file is modified while mapped. This is
+	// as unlikely as the file being modified while read in when not mapped.
+	unsafe { Mmap::map(&file) }.map(Cursor::new)
+}
+
+/// Buffers `file`.
+#[cfg(not(feature = "mmap"))]
+fn buffer(file: File) -> io::Result<BufReader<File>> {
+	Ok(BufReader::new(file))
+}
+
 /// Dumps known count of bytes from reader as MIF to writer.
 ///
 ///   * `lines`: Writer, MIF is written to.
 ///   * `bytes`: Reader, bytes are read from.
 ///   * `count`: Count of bytes to read.
+///   * `width`: Word width in bits from 1 to 128 or guessed, see `Width`.
+///   * `order`: LSB/MSB first (little/big-endian) or guessed, see `Order`.
+///   * `group`: Optional count of bytes per group and LSB/MSB first of the
+///     groups of mixed-endian words, see `Mif::read_grouped()`.
+///   * `format`: Output format, see `Format`.
+///   * `ecc`: Optional check bits concatenated to words, see `Ecc`.
+///   * `style`: How to write MIF, see `Style`, where a given `first` is
+///     replaced by the possibly guessed LSB/MSB first the bytes are read with.
+///
+/// Returns the MIF as written, see `read_dump()` to read it only. Errors if an
+/// option of `style` is given which is irrelevant to the `format`.
+#[allow(clippy::too_many_arguments)]
+pub fn dump(
+	lines: &mut dyn Write,
+	bytes: &mut dyn Read,
+	count: usize,
+	width: Width,
+	order: Order,
+	group: Option<(usize, First)>,
+	format: Format,
+	ecc: Option<Ecc>,
+	style: &Style,
+) -> Result<Mif<u128>> {
+	ensure_style(Target::Format(format), style)?;
+	let mut buffer = Vec::new();
+	let mut cursor;
+	let guess = order == Order::Guess || width == Width::Guess;
+	let bytes: &mut dyn Read = if guess {
+		bytes.read_to_end(&mut buffer).context("Cannot read input")?;
+		cursor = Cursor::new(&buffer);
+		&mut cursor
+	} else {
+		bytes
+	};
+	let width = match width {
+		Width::Given(width) => width,
+		Width::Guess => guess_width(&buffer, match order {
+			Order::Given(first) => first,
+			Order::Guess => First::default(),
+		})?,
+	};
+	let first = match order {
+		Order::Given(first) => first,
+		Order::Guess => First::guess(&buffer, width)?,
+	};
+	let mut mif = read_dump(bytes, count, width, first, group)?;
+	if let Some(ecc) = ecc {
+		mif = mif.with_parity(ecc).context("Cannot add check bits")?;
+	}
+	let style = &Style { first: style.first.map(|_| first), ..*style };
+	mif.write_as(lines, format, first, style).context("Cannot write MIF")?;
+	Ok(mif)
+}
+
+/// Errors if `style` has an option given which is irrelevant to output format
+/// `to`, see `dump()` and `convert()`.
+fn ensure_style(to: Target, style: &Style) -> Result<()> {
+	let records = [Format::Ihex, Format::Srec].map(Target::Format)
+		.contains(&to);
+	ensure!(records || style.record_bytes.is_none(),
+		"Record bytes irrelevant to formats");
+	let ranges = [Format::Mif, Format::Readmemh, Format::Vhdl]
+		.map(Target::Format).contains(&to);
+	ensure!(ranges || style.min_run.is_none(),
+		"Minimum run irrelevant to formats");
+	let cased = [Format::Mif, Format::Coe, Format::Readmemh, Format::Vhdl,
+		Format::C].map(Target::Format).contains(&to);
+	let case = if cased { HexCase::default() } else { style.case };
+	let rest = Style { record_bytes: None, min_run: None, case, ..*style };
+	ensure!(to == Target::Format(Format::Mif) || rest == Style::default(),
+		"Style irrelevant to non-MIF output");
+	Ok(())
+}
+
+/// Converts input of format `from` to output of format `to`.
+///
+///   * `lines`: Writer, output is written to.
+///   * `bytes`: Reader, input is read from.
+///   * `from`: Input format, see `Origin`.
+///   * `to`: Output format, see `Target`.
+///   * `width`: Word width in bits from 1 to 128, required unless from MIF.
+///   * `first`: LSB/MSB first (little/big-endian) of byte-oriented formats,
+///     defaults to the one commented in MIF, see `parse::first()`, or `Lsb`.
+///   * `strict`: Whether to reject non-canonical MIF, see `Mif::parse()`.
+///   * `style`: How to write MIF, see `Style`.
+///
+/// Errors if an option is given which is irrelevant to the formats.
+#[allow(clippy::too_many_arguments)]
+pub fn convert(
+	lines: &mut dyn Write,
+	bytes: &mut dyn Read,
+	from: Origin,
+	to: Target,
+	width: Option<usize>,
+	first: Option<First>,
+	strict: bool,
+	style: &Style,
+) -> Result<()> {
+	let binary = from != Origin::Mif;
+	let bytewise = to.is_bytewise();
+	ensure!(binary || width.is_none(), "Width irrelevant to MIF input");
+	ensure!(binary || bytewise || first.is_none(),
+		"LSB/MSB first irrelevant to formats");
+	ensure!(!binary || !strict, "Strictness irrelevant to binary input");
+	ensure_style(to, style)?;
+	let mut input = Vec::new();
+	bytes.read_to_end(&mut input).context("Cannot read input")?;
+	let text = |input| String::from_utf8(input).context("Invalid UTF-8 input");
+	let (mif, first) = match from {
+		Origin::Mif => {
+			let text = text(input)?;
+			let mif = if strict {
+				Mif::<u128>::parse_strict(&text)
+			} else {
+				Mif::parse(&text)
+			}.context("Cannot parse input")?;
+			let first = match first {
+				Some(first) => first,
+				None => parse::first(&text).context("Cannot parse input")?
+					.unwrap_or_default(),
+			};
+			(mif, first)
+		},
+		_ => {
+			let width = width.context("Missing width of binary input")?;
+			let first = first.unwrap_or_default();
+			let input = match from {
+				Origin::Ihex => parse::ihex(&text(input)?),
+				Origin::Srec => parse::srec(&text(input)?),
+				_ => Ok(input),
+			}.context("Cannot parse input")?;
+			let mut mif = Mif::<u128>::new(width)?;
+			mif.append_bytes(&input, first).context("Cannot read input")?;
+			(mif, first)
+		},
+	};
+	to.write(&mif, lines, first, style).context("Cannot write output")
+}
+
+/// Input format of `convert()`.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Origin {
+	/// Raw bytes as `"bin"`.
+	Bin,
+	/// MIF as `"mif"`, see `Mif::parse()`.
+	Mif,
+	/// Intel HEX as `"ihex"`, see `parse::ihex()`.
+	Ihex,
+	/// Motorola S-record as `"srec"`, see `parse::srec()`.
+	Srec,
+}
+
+impl FromStr for Origin {
+	type Err = anyhow::Error;
+
+	fn from_str(from: &str) -> Result<Self> {
+		match from {
+			"bin" => Ok(Origin::Bin),
+			"mif" => Ok(Origin::Mif),
+			"ihex" => Ok(Origin::Ihex),
+			"srec" => Ok(Origin::Srec),
+			_ => Err(anyhow!(
+				"Valid values are `bin`, `mif`, `ihex`, and `srec`")),
+		}
+	}
+}
+
+/// Output format of `convert()`.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Target {
+	/// Raw bytes as `"bin"`, see `Mif::write_bytes()`.
+	Bin,
+	/// Any other output format, see `Format`.
+	Format(Format),
+}
+
+impl FromStr for Target {
+	type Err = anyhow::Error;
+
+	fn from_str(from: &str) -> Result<Self> {
+		match from {
+			"bin" => Ok(Target::Bin),
+			_ => from.parse().map(Target::Format)
+				.map_err(|_| anyhow!("Valid values are `bin`, `mif`, `ihex`, \
+					`srec`, `coe`, `readmemh`, `vhdl`, and `c`")),
+		}
+	}
+}
+
+impl Target {
+	/// Output format selected by the extension of `path`.
+	///
+	/// Selects `Bin` for `.bin` case-insensitively, otherwise the `Format`
+	/// selected by `Format::from_path()`.
+	pub fn from_path(path: &Path) -> Self {
+		match path.extension() {
+			Some(extension) if extension.eq_ignore_ascii_case("bin") =>
+				Target::Bin,
+			_ => Target::Format(Format::from_path(path)),
+		}
+	}
+	/// Whether output is byte-oriented, that is depends on LSB/MSB first.
+	pub fn is_bytewise(&self) -> bool {
+		match *self {
+			Target::Bin => true,
+			Target::Format(to) => [Format::Ihex, Format::Srec].contains(&to),
+		}
+	}
+	/// Writes `mif` in this format to writer, see `Mif::write_as()`.
+	pub fn write(
+		&self,
+		mif: &Mif<u128>,
+		lines: &mut dyn Write,
+		first: First,
+		style: &Style,
+	) -> Result<()> {
+		match *self {
+			Target::Bin => mif.write_bytes(lines, first),
+			Target::Format(format) => mif.write_as(lines, format, first, style),
+		}.map_err(Into::into)
+	}
+}
+
+/// Prints information about known count of bytes from reader as MIF.
+///
+///   * `lines`: Writer, information is written to as `key = value` lines.
+///   * `bytes`: Reader, bytes are read from.
+///   * `count`: Count of bytes to read.
 ///   * `width`: Word width in bits from 1 to 128.
 ///   * `first`: LSB/MSB first (little/big-endian).
-pub fn dump(
+///   * `word`: Optional word whose count of addresses is written as well.
+pub fn info(
 	lines: &mut dyn Write,
 	bytes: &mut dyn Read,
 	count: usize,
 	width: usize,
 	first: First,
+	word: Option<u128>,
 ) -> Result<()> {
+	let mif = read_dump(bytes, count, width, first, None)?;
+	writeln!(lines, "\
+		width = {}\n\
+		depth = {}\n\
+		depth-bytes = {}\n\
+		size-bytes = {}\n\
+		run-count = {}\n\
+		compression-ratio = {}",
+		mif.width(), mif.depth(), mif.depth_bytes(),
+		mif.size_bytes(&Style::default())?, mif.run_count(),
+		mif.compression_ratio())?;
+	if let Some((word, ratio)) = mif.dominant_word() {
+		writeln!(lines, "dominant-word = {:#x}\ndominant-ratio = {}",
+			word, ratio)?;
+	}
+	if let Some(word) = word {
+		writeln!(lines, "word-count = {}\nword-ratio = {}",
+			mif.count_word(word), mif.fill_ratio_of(word))?;
+	}
+	Ok(())
+}
+
+/// Reads known count of bytes from reader as MIF as `dump()` does.
+///
+///   * `bytes`: Reader, bytes are read from.
+///   * `count`: Count of bytes to read, a multiple of the word alignment.
+///   * `width`: Word width in bits from 1 to 128.
+///   * `first`: LSB/MSB first (little/big-endian).
+///   * `group`: Optional count of bytes per group read LSB/MSB `first` and
+///     the LSB/MSB first of the groups, see `Mif::read_grouped()`.
+///
+/// Returns the MIF without writing it, see `Mif::write_as()` otherwise.
+pub fn read_dump(
+	bytes: &mut dyn Read,
+	count: usize,
+	width: usize,
+	first: First,
+	group: Option<(usize, First)>,
+) -> Result<Mif<u128>> {
 	let mut mif = Mif::<u128>::new(width)?;
 	let align = mif.align();
 	let depth = count / align;
-	ensure!(depth * align == count, "No integral multiple of word width");
-	mif.read(bytes, depth, first).context("Cannot read input")
-		.and_then(|()| mif.write(lines, false).context("Cannot write MIF"))
+	ensure!(depth * align == count,
+		"Input of {} B no integral multiple of {} B words", count, align);
+	match group {
+		Some((group, groups)) =>
+			mif.read_grouped(bytes, depth, first, group, groups),
+		None => mif.read(bytes, depth, first),
+	}.context("Cannot read input")?;
+	Ok(mif)
 }
 
-/// Load TOML from file or standard input `"-"` as `Files`.
-pub fn load(input: &dyn AsRef<Path>) -> Result<Files> {
-	let input = input.as_ref();
-	let mut file: Box<dyn Read> = if input == Path::new("-") {
-		Box::new(stdin())
-	} else {
-		Box::new(OpenOptions::new().read(true).open(&input).map(BufReader::new)
-			.with_context(|| format!("Cannot open `{}`", input.display()))?)
-	};
+/// Word width in bits or guessed.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Width {
+	/// Given word width in bits from 1 to 128.
+	Given(usize),
+	/// Guessed word width as `"auto"`, see `guess_width()`.
+	Guess,
+}
+
+impl FromStr for Width {
+	type Err = anyhow::Error;
+
+	fn from_str(from: &str) -> Result<Self> {
+		match from {
+			"auto" => Ok(Width::Guess),
+			_ => from.parse().map(Width::Given)
+				.map_err(|_| anyhow!("Valid values are bits and `auto`")),
+		}
+	}
+}
+
+/// Guesses word width in bits of `bytes` read LSB/MSB `first` among 8, 16, 24,
+/// and 32 bits.
+///
+/// This is a heuristic choosing among the widths dividing the count of bytes
+/// the one with the lowest `Mif::compression_ratio()`, that is the fewest runs
+/// per word, preferring the narrower width on par. As 8 bits divide any count,
+/// it is always a candidate and chosen for empty or incompressible bytes.
+pub fn guess_width(bytes: &[u8], first: First) -> Result<usize> {
+	let mut guess = (f64::INFINITY, 8);
+	for width in [8, 16, 24, 32] {
+		let align = width / 8;
+		if bytes.len().is_multiple_of(align) {
+			let mut mif = Mif::<u128>::new(width)?;
+			mif.read(&mut &bytes[..], bytes.len() / align, first)?;
+			let ratio = mif.compression_ratio();
+			if ratio < guess.0 {
+				guess = (ratio, width);
+			}
+		}
+	}
+	Ok(guess.1)
+}
+
+/// LSB/MSB first (little/big-endian) or guessed.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Order {
+	/// Given LSB/MSB first as `"lsb"`, `"msb"`, or `"native"`.
+	Given(First),
+	/// Guessed LSB/MSB first as `"auto"`, see `First::guess()`.
+	Guess,
+}
+
+impl FromStr for Order {
+	type Err = anyhow::Error;
+
+	fn from_str(from: &str) -> Result<Self> {
+		match from {
+			"auto" => Ok(Order::Guess),
+			_ => from.parse().map(Order::Given)
+				.map_err(|_| anyhow!(
+					"Valid values are `lsb`, `msb`, `native`, and `auto`")),
+		}
+	}
+}
+
+/// Load TOML from file or standard input `"-"` as `Files` and `Mifs`.
+///
+/// An optional top-level `[defaults]` table provides `first`, `width`, and
+/// `input-format` to memory areas omitting them. An optional top-level
+/// `[mifs]` table declares the expectations of joined MIFs, see `verify()`.
+/// An optional top-level `include` array lists TOML files loaded beforehand
+/// as in `include = ["common.toml", "banks.toml"]`, resolved relative to the
+/// including file and recursively including further files. Hence, a binary
+/// can be named neither `defaults`, `mifs`, nor `include`.
+///
+/// Defaults apply to the memory areas of their own file only. Binaries and
+/// expectations of MIFs must be unique across files, erroring otherwise and
+/// on include cycles. Included binaries precede the including file's ones.
+///
+/// The path of a file is expanded, see `expand()`.
+pub fn load(input: &dyn AsRef<Path>) -> Result<(Files, Mifs)> {
+	load_included(input.as_ref(), &mut Vec::new())
+}
+
+/// Loads TOML from `toml` reader as `Files` and `Mifs` as if located in
+/// `toml_dir`, see `load()`.
+///
+/// Intended for piped TOML whose included files are resolved relative to
+/// `toml_dir` instead of the current working directory.
+pub fn load_from(toml: &mut dyn Read, toml_dir: &dyn AsRef<Path>)
+-> Result<(Files, Mifs)> {
+	let toml_dir = expand(toml_dir)?;
+	read_included(toml, Path::new("-"), toml_dir.join("-"), &toml_dir,
+		&mut Vec::new())
+}
+
+/// Loads TOML included by the `chain` of files, see `load()`.
+fn load_included(input: &Path, chain: &mut Vec<PathBuf>)
+-> Result<(Files, Mifs)> {
+	if input == Path::new("-") {
+		return read_included(&mut stdin(), input, input.to_path_buf(),
+			Path::new(""), chain);
+	}
+	let input = &expand(&input)?;
+	let mut file = OpenOptions::new().read(true).open(input)
+		.map(BufReader::new)
+		.with_context(|| format!("Cannot open `{}`", input.display()))?;
+	let path = input.canonicalize().unwrap_or_else(|_| input.clone());
+	let dir = input.parent().unwrap_or(Path::new(""));
+	read_included(&mut file, input, path, dir, chain)
+}
+
+/// Reads TOML of `input` at `path` from `toml` reader resolving includes
+/// relative to `dir`, see `load_included()`.
+fn read_included(
+	toml: &mut dyn Read,
+	input: &Path,
+	path: PathBuf,
+	dir: &Path,
+	chain: &mut Vec<PathBuf>,
+) -> Result<(Files, Mifs)> {
+	ensure!(!chain.contains(&path), "Include cycle {}", chain.iter()
+		.chain(once(&path)).map(|path| format!("`{}`", path.display()))
+		.collect::<Vec<_>>().join(" -> "));
 	let mut string = String::new();
-	file.read_to_string(&mut string)
-		.with_context(|| format!("Cannot read `{}`", input.display()))
-	.and_then(|_count| toml::from_str::<Files>(&string)
-		.with_context(|| format!("Cannot load `{}`", input.display())))
+	toml.read_to_string(&mut string)
+		.with_context(|| format!("Cannot read `{}`", input.display()))?;
+	chain.push(path);
+	let loaded = toml::from_str::<Value>(&string).map_err(Into::into)
+		.and_then(includes).and_then(|(value, includes)| {
+			let (value, mifs) = mifs(value)?;
+			let files = defaults(value)?.try_into::<Files>()?;
+			let (mut all_files, mut all_mifs) = (Files::new(), Mifs::new());
+			for include in includes {
+				let include = dir.join(expand(&include)?);
+				let (files, mifs) = load_included(&include, chain)?;
+				merge(&mut all_files, &mut all_mifs, files, mifs)?;
+			}
+			merge(&mut all_files, &mut all_mifs, files, mifs)?;
+			Ok((all_files, all_mifs))
+		}).with_context(|| format!("Cannot load `{}`", input.display()));
+	chain.pop();
+	loaded
+}
+
+/// Splits off `include` array.
+fn includes(mut value: Value) -> Result<(Value, Vec<PathBuf>)> {
+	let files = value.as_table_mut().context("No table of binaries")?;
+	let includes = match files.remove("include") {
+		Some(includes) => includes.try_into::<Vec<PathBuf>>()?,
+		None => Vec::new(),
+	};
+	Ok((value, includes))
+}
+
+/// Merges `files` and `mifs` into `all_files` and `all_mifs` ensuring unique
+/// binaries and expectations of MIFs.
+fn merge(all_files: &mut Files, all_mifs: &mut Mifs, files: Files, mifs: Mifs)
+-> Result<()> {
+	for (bin_path, areas) in files {
+		ensure!(!all_files.contains_key(&bin_path),
+			"Binary `{}` loaded twice", bin_path.display());
+		all_files.insert(bin_path, areas);
+	}
+	for (mif_path, expect) in mifs {
+		ensure!(!all_mifs.contains_key(&mif_path),
+			"Expectation of `{}` loaded twice", mif_path.display());
+		all_mifs.insert(mif_path, expect);
+	}
+	Ok(())
+}
+
+/// Splits off `[mifs]` table.
+fn mifs(mut value: Value) -> Result<(Value, Mifs)> {
+	let files = value.as_table_mut().context("No table of binaries")?;
+	let mifs = match files.remove("mifs") {
+		Some(mifs) => mifs.try_into::<Mifs>()?,
+		None => Mifs::new(),
+	};
+	Ok((value, mifs))
+}
+
+/// Merges `[defaults]` table into memory areas omitting its keys.
+fn defaults(mut value: Value) -> Result<Value> {
+	let files = value.as_table_mut().context("No table of binaries")?;
+	if let Some(defaults) = files.remove("defaults") {
+		let defaults = defaults.as_table().context("No table of defaults")?;
+		for key in defaults.keys() {
+			ensure!(["first", "width", "input-format"].contains(&key.as_str()),
+				"Invalid key `{}` in defaults", key);
+		}
+		let areas = files.iter_mut()
+			.filter_map(|(_bin_path, areas)| areas.as_array_mut())
+			.flat_map(|areas| areas.iter_mut().filter_map(Value::as_table_mut));
+		for area in areas {
+			for (key, val) in defaults {
+				if !area.contains_key(key) {
+					area.insert(key.clone(), val.clone());
+				}
+			}
+		}
+	}
+	Ok(value)
+}
+
+/// Saves `Manifest` as TOML to file or standard output `"-"`.
+///
+/// The path of a file is expanded, see `expand()`.
+pub fn save(manifest: &Manifest, output: &dyn AsRef<Path>) -> Result<()> {
+	let output = output.as_ref();
+	let string = toml::to_string(manifest)
+		.with_context(|| format!("Cannot save `{}`", output.display()))?;
+	let mut file = create(&output)?;
+	file.write_all(string.as_bytes()).and_then(|()| file.flush())
+		.with_context(|| format!("Cannot write `{}`", output.display()))
+}
+
+/// Serializes `Files` as TOML loadable by `load()`.
+///
+/// Unlike `toml::to_string()`, writes the keys of each memory area as inline
+/// values, so arrays mixing words or bits with ranges of them as in `skips` and
+/// `field-map` are written as they are read instead of as arrays of tables.
+pub fn to_toml(files: &Files) -> Result<String> {
+	let mut toml = String::new();
+	for (bin_path, areas) in files {
+		let bin_path = Value::String(bin_path.display().to_string());
+		for area in areas {
+			toml += &format!("[[{}]]\n", bin_path);
+			if let Value::Table(keys) = Value::try_from(area)? {
+				for (key, value) in keys {
+					toml += &format!("{} = {}\n", key, inline(&value));
+				}
+			}
+		}
+	}
+	Ok(toml)
+}
+
+/// TOML `value` with tables written inline, see `to_toml()`.
+fn inline(value: &Value) -> String {
+	match value {
+		Value::Array(array) => format!("[{}]",
+			array.iter().map(inline).collect::<Vec<_>>().join(", ")),
+		Value::Table(table) => format!("{{ {} }}", table.iter()
+			.map(|(key, value)| format!("{} = {}", key, inline(value)))
+			.collect::<Vec<_>>().join(", ")),
+		value => value.to_string(),
+	}
+}
+
+/// Creates file or opens standard output `"-"` as buffered writer.
+///
+/// The path of a file is expanded, see `expand()`.
+pub fn create(output: &dyn AsRef<Path>) -> Result<Box<dyn Write>> {
+	let output = output.as_ref();
+	Ok(if output == Path::new("-") {
+		Box::new(stdout())
+	} else {
+		let output = &expand(&output)?;
+		Box::new(OpenOptions::new().write(true).create(true).truncate(true)
+			.open(output).map(BufWriter::new)
+			.with_context(|| format!("Cannot open `{}`", output.display()))?)
+	})
+}
+
+/// Verifies joined MIFs of `manifest` against their expectations of `mifs`.
+///
+/// Guards against forgetting to join a memory area or against any change of
+/// the joined MIFs, see `Expect`.
+pub fn verify(manifest: &Manifest, mifs: &Mifs) -> Result<()> {
+	for (mif_path, expect) in mifs {
+		let joined = manifest.get(mif_path).with_context(||
+			format!("No areas joined to `{}`", mif_path.display()))?;
+		if let Some(depth) = expect.depth {
+			ensure!(joined.depth == depth, "Depth {} of `{}` instead of {}",
+				joined.depth, mif_path.display(), depth);
+		}
+		if let Some(crc32) = expect.crc32 {
+			ensure!(joined.crc32 == crc32,
+				"CRC-32 {:#010x} of `{}` instead of {:#010x}",
+				joined.crc32, mif_path.display(), crc32);
+		}
+	}
+	Ok(())
+}
+
+/// Sorts memory areas of every binary of `files` by their byte offsets.
+///
+/// Errors if any memory area lacks its byte offset. Overlaps and gaps between
+/// memory areas are detected when joining them, see `join()`.
+pub fn sort_areas(files: &mut Files) -> Result<()> {
+	for (bin_path, areas) in files {
+		for (index, area) in areas.iter().enumerate() {
+			ensure!(area.at.is_some(), "Missing offset of area {} in `{}`",
+				index, bin_path.display());
+		}
+		areas.sort_by_key(|area| area.at);
+	}
+	Ok(())
+}
+
+/// Bounds the count of threads joining MIFs to `jobs`, see `join()`.
+///
+/// Returns a warning to print if `jobs` is ignored, i.e., without the
+/// `parallel` feature.
+#[cfg(feature = "parallel")]
+pub fn jobs(jobs: usize) -> Result<Option<&'static str>> {
+	ThreadPoolBuilder::new().num_threads(jobs).build_global()
+		.context("Cannot build thread pool")?;
+	Ok(None)
+}
+
+/// Bounds the count of threads joining MIFs to `jobs`, see `join()`.
+///
+/// Returns a warning to print if `jobs` is ignored, i.e., without the
+/// `parallel` feature.
+#[cfg(not(feature = "parallel"))]
+pub fn jobs(_jobs: usize) -> Result<Option<&'static str>> {
+	Ok(Some("Ignoring jobs without `parallel` feature"))
+}
+
+/// Count of words to skip beyond which they are searched as a set.
+const SKIPS_SET: usize = 16;
+
+/// Reads memory `area` from `bytes` into `mif` as `join()` does.
+///
+/// The words of the `area` are read with its `first`, `width`, `depth`, and
+/// optional sentinel word `until` either aligned to bytes or `packed` and
+/// possibly reassembled by its `field_map`. A skipped area is verified to
+/// contain only its words to skip whereas a joined area is tiled by its
+/// optional `repeat`. Either way, the words are joined to `mif` of the same
+/// width.
+///
+/// Returns the count of bytes read.
+pub fn read_area(mif: &mut Mif<u128>, bytes: &mut dyn Read, area: &Area)
+-> Result<usize> {
+	let Area {
+		first, width, depth, until, packed, ref field_map, repeat, ref instr, ..
+	} = *area;
+	ensure!(mif.width() == width, "Different width {} of area to join {}",
+		width, mif.width());
+	let mut tile = Mif::new(width)?;
+	ensure!(!packed || until.is_none(), "Sentinel word of packed area");
+	ensure!(field_map.is_none() || until.is_none() && !packed,
+		"Field map of packed area or of area with sentinel word");
+	match (until, field_map) {
+		(Some(until), _) => tile
+			.read_until(bytes, until.as_word(), first, true).map(drop),
+		(None, Some(map)) => tile.read_mapped(bytes, depth, first,
+			&map.iter().flat_map(Field::bits).collect::<Vec<_>>()),
+		(None, None) if packed => tile.read_packed(bytes, depth, first),
+		(None, None) => tile.read(bytes, depth, first),
+	}?;
+	ensure!(tile.depth() <= depth, "Sentinel word beyond depth");
+	match instr {
+		Skips(skips) => {
+			ensure!(repeat.is_none(), "Repeat of skipped area");
+			if !skips.is_empty() {
+				let (words, ranges) = skips.iter()
+					.partition::<Vec<_>, _>(|skip| skip.is_word());
+				let words = words.into_iter().map(Skip::first)
+					.collect::<Vec<_>>();
+				// Searches linearly unless there are many words to skip.
+				let set = (words.len() > SKIPS_SET)
+					.then(|| words.iter().copied().collect::<BTreeSet<_>>());
+				let contains = |word| match &set {
+					Some(set) => set.contains(word),
+					None => words.contains(word),
+				};
+				ensure!(tile.words().iter().all(|(word, _bulk)|
+					contains(word) || ranges.iter()
+						.any(|range| range.contains(*word))),
+					"Invalid word to skip");
+			}
+		},
+		Joins(_joins) => if let Some(repeat) = repeat {
+			ensure!(repeat > 0, "Zero repeat");
+			ensure!(tile.depth().checked_mul(repeat).is_some(),
+				"Repeat exhausts address space");
+		},
+	}
+	for _ in 0..repeat.unwrap_or(1) {
+		mif.join(&tile)?;
+	}
+	Ok(if packed { (tile.depth() * width).div_ceil(8) }
+		else { tile.depth_bytes() })
 }
 
 /// Joins memory areas of binary `Files` as MIFs.
 ///
 ///   * `files`: Binary files split into memory areas, see `Files`.
 ///   * `paths`: Prefix paths for input binaries and output MIFs in given order.
-///   * `areas`: Whether to comment memory areas, see `write()`.
+///   * `style`: How to write MIFs, see `Style`.
+///   * `limit`: Optional maximum depth of MIFs in words.
+///
+/// Relative paths of binaries and MIFs are resolved against their prefix
+/// paths which in turn are resolved against the current working directory if
+/// relative, regardless of where `files` have been loaded from. All of these
+/// paths are expanded beforehand, see `expand()`.
+///
+/// Equals `join_with()` using the file system via `Dirs`.
 pub fn join(
 	files: &Files,
 	paths: (&dyn AsRef<Path>, &dyn AsRef<Path>),
-	areas: bool,
-) -> Result<()> {
+	style: &Style,
+	limit: Option<usize>,
+) -> Result<Manifest> {
+	let mut dirs = Dirs { bins: expand(paths.0)?, mifs: expand(paths.1)? };
+	join_with(files, &mut dirs, style, limit)
+}
+
+/// Joins memory areas of binary `Files` as MIFs using given `Storage`.
+///
+///   * `files`: Binary files split into memory areas, see `Files`.
+///   * `storage`: Storage opening binaries and creating MIFs, see `Storage`.
+///   * `style`: How to write MIFs, see `Style`.
+///   * `limit`: Optional maximum depth of MIFs in words.
+///
+/// Memory areas are joined in the order of their binaries followed by their
+/// order within each binary as given by `files`. Hence, the same `files`
+/// always result in byte-identical MIFs. Sort `files` beforehand to join
+/// memory areas in the order of their binaries' paths instead.
+///
+/// MIFs are written in parallel with the `parallel` feature, see `jobs()`,
+/// without affecting their content or the order of the `Manifest`. They are
+/// written in the format selected by their extension, see
+/// `Target::from_path()`, with words of byte-oriented formats written LSB/MSB
+/// first as their memory areas have been read, erroring if they differ. Hence,
+/// listing say `rom.mif` and `rom.bin` writes the same content as MIF and as
+/// raw bytes.
+///
+/// Returns the `Manifest` of the joined MIFs.
+pub fn join_with(
+	files: &Files,
+	storage: &mut dyn Storage,
+	style: &Style,
+	limit: Option<usize>,
+) -> Result<Manifest> {
 	let mut mifs = IndexMap::new();
 	for (bin_path, areas) in files {
-		let mut abs_path = paths.0.as_ref().to_path_buf();
-		abs_path.push(&bin_path);
-		let mut bin_file = OpenOptions::new()
-			.read(true).open(&abs_path).map(BufReader::new)
-			.with_context(|| format!("Cannot open `{}`", abs_path.display()))?;
-		for &Area { first, width, depth, ref instr } in areas {
+		#[cfg(feature = "trace")]
+		let _span = info_span!("bin", path = %bin_path.display()).entered();
+		let input = areas.first().map_or(Input::Bin, |area| area.input);
+		ensure!(areas.iter().all(|area| area.input == input),
+			"Different input formats of `{}`", bin_path.display());
+		let mut bin_file = match storage.open_seekable(bin_path)? {
+			Some(bin_file) => Bin::Seekable(bin_file),
+			None => Bin::Readable(storage.open(bin_path)?),
+		};
+		if input != Input::Bin {
+			let mut text = String::new();
+			bin_file.read_to_string(&mut text).with_context(||
+				format!("Cannot read `{}`", bin_path.display()))?;
+			let bytes = match input {
+				Input::Ihex => parse::ihex(&text),
+				_ => parse::srec(&text),
+			}.with_context(|| format!("Cannot parse `{}`",
+				bin_path.display()))?;
+			bin_file = Bin::Seekable(Box::new(Cursor::new(bytes)));
+		}
+		let mut offset = 0;
+		let mut notes = Vec::new();
+		for (index, area) in areas.iter().enumerate() {
+			let Area {
+				first, width, depth, until, repeat, ref name, ref instr, at, ..
+			} = *area;
+			if let Some(at) = at {
+				ensure!(at >= offset,
+					"Area {} at byte {} overlaps {} B in `{}`",
+					index, at, offset - at, bin_path.display());
+				ensure!(at <= offset, "Gap of {} B before area {} in `{}`",
+					at - offset, index, bin_path.display());
+			}
+			#[cfg(feature = "trace")]
+			let _span = info_span!("area", index, offset).entered();
 			let mut mif_area = Mif::new(width)?;
-			mif_area.read(&mut bin_file, depth, first)?;
+			let seek = match instr {
+				Skips(skips) => skips.is_empty() && until.is_none()
+					&& repeat.is_none(),
+				Joins(_joins) => false,
+			};
+			let sought = seek && bin_file.skip(area.bytes())
+				.with_context(|| format!(
+					"Cannot skip area {} at byte {} of `{}`",
+					index, offset, bin_path.display()))?;
+			let bytes = if sought { area.bytes() } else {
+				read_area(&mut mif_area, &mut bin_file, area)
+					.with_context(|| format!(
+						"Cannot read area {} at byte {} of `{}`",
+						index, offset, bin_path.display()))?
+			};
+			#[cfg(feature = "trace")]
+			info!(bytes, words = mif_area.depth(),
+				runs = mif_area.words().len(), "read area");
+			offset += bytes;
 			match instr {
-				Skips(skips) => if !skips.is_empty() {
-					ensure!(mif_area.words().iter()
-						.all(|&(word, _bulk)| skips.iter()
-							.any(|skip| skip.as_word() == word)),
-						"Invalid word to skip in `{}`", bin_path.display());
+				Skips(skips) => {
+					let mut note = format!("skipped {} words",
+						if sought { depth } else { mif_area.depth() });
+					if !skips.is_empty() {
+						let mut words = mif_area.words().iter()
+							.map(|&(word, _bulk)| word).collect::<Vec<_>>();
+						words.sort_unstable();
+						words.dedup();
+						let word_pads = mif_area.word_pads();
+						for (index, word) in words.into_iter().enumerate() {
+							note += if index == 0 { " of " } else { ", " };
+							note += &Hex(word, word_pads, style.case)
+								.to_string();
+						}
+					}
+					note += &format!(" in {}", bin_path.display());
+					notes.push(note);
 				},
-				Joins(joins) => for mif_path in joins {
-					if !mifs.contains_key(mif_path) {
-						let mut abs_path = paths.1.as_ref().to_path_buf();
-						abs_path.push(mif_path);
-						let mif_file = OpenOptions::new()
-							.write(true).create(true).truncate(true)
-							.open(&abs_path).map(BufWriter::new)
-							.with_context(|| format!("Cannot open `{}`",
-								abs_path.display()))?;
-						let mif = (mif_file, Mif::new(width)?);
-						assert!(mifs.insert(mif_path.clone(), mif).is_none());
+				Joins(joins) => {
+					for mif_path in joins {
+						let first = Some(first.resolve());
+						if !mifs.contains_key(mif_path) {
+							let mif_file = storage.create(mif_path)?;
+							let mif = (mif_file, Mif::new(width)?, first);
+							assert!(mifs.insert(mif_path.clone(), mif)
+								.is_none());
+						}
+						let (_mif_file, mif_data, mif_first) =
+							&mut mifs[mif_path];
+						ensure!(mif_data.width() == width,
+							"Different width to join `{}`", mif_path.display());
+						if *mif_first != first {
+							*mif_first = None;
+						}
+						notes.iter()
+							.for_each(|note| mif_data.note(note.clone()));
+						mif_data.area(name.as_ref()
+							.map_or_else(|| bin_path.clone(), PathBuf::from));
+						match limit {
+							Some(limit) => mif_data
+								.join_bounded(&mif_area, limit).map(drop),
+							None => mif_data.join(&mif_area),
+						}.with_context(|| format!(
+							"Cannot join area {} of `{}` to `{}`",
+							index, bin_path.display(), mif_path.display()))?;
 					}
-					let (_mif_file, mif_data) = &mut mifs[mif_path];
-					ensure!(mif_data.width() == width,
-						"Different width to join `{}`", mif_path.display());
-					mif_data.area(bin_path.clone());
-					mif_data.join(&mif_area)?;
+					notes.clear();
 				},
 			}
 		}
 		let mut bin_data = Vec::new();
 		bin_file.read_to_end(&mut bin_data)?;
-		ensure!(bin_data.is_empty(),
-			"{} B left over in `{}`", bin_data.len(), bin_path.display());
+		ensure!(bin_data.is_empty(), "{} B left over at byte {} of `{}`",
+			bin_data.len(), offset, bin_path.display());
+	}
+	let mut mif_files = Vec::with_capacity(mifs.len());
+	let mut mif_datas = Vec::with_capacity(mifs.len());
+	for (mif_path, (mif_file, mif_data, first)) in mifs {
+		let target = Target::from_path(&mif_path);
+		ensure!(first.is_some() || !target.is_bytewise(),
+			"Different LSB/MSB first to join `{}`", mif_path.display());
+		mif_files.push((mif_path, mif_file));
+		mif_datas.push((target, first.unwrap_or_default(), mif_data));
 	}
-	for (mif_path, (mut mif_file, mif_data)) in mifs {
-		mif_data.write(&mut mif_file, areas)
+	#[cfg(feature = "parallel")]
+	let mifs = mif_datas.into_par_iter();
+	#[cfg(not(feature = "parallel"))]
+	let mifs = mif_datas.into_iter();
+	let mifs = mifs.map(|(target, first, mif_data)| {
+		let mut lines = Vec::new();
+		target.write(&mif_data, &mut lines, first, style)
+			.map(|()| (mif_data, lines))
+	}).collect::<Vec<_>>();
+	let mut manifest = Manifest::new();
+	for ((mif_path, mut mif_file), mif) in mif_files.into_iter().zip(mifs) {
+		let (mif_data, lines) = mif?;
+		mif_file.write_all(&lines).and_then(|()| mif_file.flush())
 			.with_context(|| format!("Cannot write `{}`", mif_path.display()))?;
+		#[cfg(feature = "trace")]
+		info!(path = %mif_path.display(), words = mif_data.depth(),
+			bytes = lines.len(), "wrote MIF");
+		manifest.insert(mif_path, Joined {
+			width: mif_data.width(),
+			depth: mif_data.depth(),
+			crc32: crc32(0, &lines),
+			areas: mif_data.areas().iter().map(|(addr, path)|
+				Source { addr: *addr, path: path.clone() }).collect(),
+		});
+	}
+	Ok(manifest)
+}
+
+/// Storage of binaries and MIFs, see `join_with()`.
+///
+/// Enables joins in memory or over custom IO, see `Dirs` for the file system.
+pub trait Storage {
+	/// Opens binary at `path` as given by `Files` for reading.
+	fn open(&mut self, path: &Path) -> Result<Box<dyn Read>>;
+	/// Opens binary at `path` as given by `Files` for reading and seeking.
+	///
+	/// Lets memory areas skipped without verification be sought past instead
+	/// of being read. Defaults to `None` falling back to `open()`.
+	fn open_seekable(&mut self, _path: &Path)
+	-> Result<Option<Box<dyn ReadSeek>>> {
+		Ok(None)
+	}
+	/// Creates MIF at `path` as given by `Files` for writing.
+	fn create(&mut self, path: &Path) -> Result<Box<dyn Write>>;
+}
+
+/// Reader which is seekable as well, see `Storage::open_seekable()`.
+pub trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
+
+/// Binary either seekable or readable only.
+enum Bin {
+	Seekable(Box<dyn ReadSeek>),
+	Readable(Box<dyn Read>),
+}
+
+impl Bin {
+	/// Seeks past `count` bytes if seekable, erroring beyond the end.
+	///
+	/// Returns whether it has been sought.
+	fn skip(&mut self, count: usize) -> Result<bool> {
+		match self {
+			Bin::Seekable(bin) => {
+				let at = bin.stream_position()?;
+				let end = bin.seek(SeekFrom::End(0))?;
+				let to = at + count as u64;
+				bin.seek(SeekFrom::Start(to.min(end)))?;
+				ensure!(to <= end, "Missing {} B", to - end);
+				Ok(true)
+			},
+			Bin::Readable(_bin) => Ok(false),
+		}
+	}
+}
+
+impl Read for Bin {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		match self {
+			Bin::Seekable(bin) => bin.read(buf),
+			Bin::Readable(bin) => bin.read(buf),
+		}
+	}
+}
+
+/// File system storage of binaries and MIFs in directories.
+///
+/// Relative paths are resolved against the directories after being expanded,
+/// see `expand()`.
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct Dirs {
+	/// Input directory of binaries.
+	pub bins: PathBuf,
+	/// Output directory of MIFs.
+	pub mifs: PathBuf,
+}
+
+impl Dirs {
+	/// Opens binary at `path` relative to `bins`.
+	fn bin(&self, path: &Path) -> Result<BufReader<File>> {
+		let path = self.bins.join(expand(&path)?);
+		let file = OpenOptions::new().read(true).open(&path)
+			.with_context(|| format!("Cannot open `{}`", path.display()))?;
+		Ok(BufReader::new(file))
+	}
+}
+
+impl Storage for Dirs {
+	fn open(&mut self, path: &Path) -> Result<Box<dyn Read>> {
+		Ok(Box::new(self.bin(path)?))
+	}
+	fn open_seekable(&mut self, path: &Path)
+	-> Result<Option<Box<dyn ReadSeek>>> {
+		Ok(Some(Box::new(self.bin(path)?)))
+	}
+	fn create(&mut self, path: &Path) -> Result<Box<dyn Write>> {
+		let path = self.mifs.join(expand(&path)?);
+		let file = OpenOptions::new().write(true).create(true).truncate(true)
+			.open(&path)
+			.with_context(|| format!("Cannot open `{}`", path.display()))?;
+		Ok(Box::new(BufWriter::new(file)))
 	}
-	Ok(())
+}
+
+/// Joined MIFs in order of creation.
+pub type Manifest = IndexMap<PathBuf, Joined>;
+
+/// Joined MIF.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Joined {
+	/// Word width in bits.
+	pub width: usize,
+	/// Depth in words.
+	pub depth: usize,
+	/// CRC-32 of MIF file.
+	pub crc32: u32,
+	/// Memory areas in given order.
+	pub areas: Vec<Source>,
+}
+
+/// Memory area of joined MIF.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Source {
+	/// Address in words.
+	pub addr: usize,
+	/// Path of binary or name of memory area.
+	pub path: PathBuf,
+}
+
+/// Expectations of joined MIFs, see `verify()`.
+pub type Mifs = IndexMap<PathBuf, Expect>;
+
+/// Expectation of joined MIF.
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Expect {
+	/// Optional depth in words the joined memory areas must add up to.
+	#[serde(default)]
+	pub depth: Option<usize>,
+	/// Optional CRC-32 of MIF file as listed in the `Manifest`.
+	///
+	/// Depends on how the MIF is written, see `Style`.
+	#[serde(default)]
+	pub crc32: Option<u32>,
 }
 
 /// Binary files split into memory areas.
+///
+/// Preserves the order of binaries and of their memory areas as loaded.
 pub type Files = IndexMap<PathBuf, Vec<Area>>;
 
 /// Memory area.
-#[derive(Debug, Eq, PartialEq, Clone, Deserialize)]
+///
+/// Serializes back to the form it deserializes from, so join specs can be
+/// emitted programmatically, with unset options omitted, see `to_toml()`.
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct Area {
 	/// LSB/MSB first (little/big-endian).
@@ -143,25 +1311,135 @@ pub struct Area {
 	/// Word width in bits from 1 to 128.
 	#[serde(default = "default_width")]
 	pub width: usize,
-	/// Depth in words.
+	/// Depth in words or maximum depth in words if reading `until` sentinel.
 	pub depth: usize,
+	/// Optional sentinel word to read until, including the sentinel word.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub until: Option<Word>,
+	/// Whether words are bit-packed back-to-back as in two 4-bit words per
+	/// byte instead of aligned to bytes, see `Mif::read_packed()`.
+	#[serde(default)]
+	pub packed: bool,
+	/// Optional field map listing the source bit or range of source bits of
+	/// every bit of a word from its least-significant bit on, reassembling
+	/// scrambled words, see `Mif::read_mapped()`. Byte-swapped 16-bit words
+	/// are mapped by `[{ from = 8, to = 15 }, { from = 0, to = 7 }]`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub field_map: Option<Vec<Field>>,
+	/// Optional count of times to repeat this memory area when joining it.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub repeat: Option<usize>,
+	/// Optional name commenting this memory area instead of its binary path.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+	/// Optional byte offset into binary verified to follow preceding area.
+	#[serde(
+		default, rename = "offset", skip_serializing_if = "Option::is_none",
+	)]
+	pub at: Option<usize>,
+	/// Format of binary, the same for all its memory areas.
+	#[serde(default, rename = "input-format")]
+	pub input: Input,
 	/// Whether to skip or join this memory area.
 	#[serde(flatten)]
 	pub instr: Instr,
 }
 
+/// Format of binary.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Input {
+	/// Raw bytes.
+	#[default]
+	Bin,
+	/// Intel HEX, see `parse::ihex()`.
+	Ihex,
+	/// Motorola S-record, see `parse::srec()`.
+	Srec,
+}
+
+impl Area {
+	/// Count of bytes of `depth` words either aligned to bytes or `packed`.
+	pub fn bytes(&self) -> usize {
+		if self.packed {
+			(self.depth * self.width).div_ceil(8)
+		} else {
+			self.depth * self.width.div_ceil(8)
+		}
+	}
+}
+
 /// Whether to skip or join a memory area.
-#[derive(Debug, Eq, PartialEq, Clone, Deserialize)]
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum Instr {
 	/// Skips memory area and ensures it contains given words only.
-	Skips(Vec<Word>),
+	Skips(Vec<Skip>),
 	/// Joins memory area to given MIFs.
 	Joins(Vec<PathBuf>),
 }
 
+/// Word or range of words to skip.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Skip {
+	/// One word as in `0xff`.
+	Word(Word),
+	/// Inclusive range of words as in `{ from = 0x00, to = 0x0f }`.
+	Range {
+		/// First word of range.
+		from: Word,
+		/// Last word of range.
+		to: Word,
+	},
+}
+
+impl Skip {
+	fn is_word(&self) -> bool {
+		matches!(self, Skip::Word(_))
+	}
+	fn first(&self) -> u128 {
+		match self {
+			Skip::Word(word) | Skip::Range { from: word, .. } => word.as_word(),
+		}
+	}
+	fn contains(&self, word: u128) -> bool {
+		match self {
+			Skip::Word(skip) => skip.as_word() == word,
+			Skip::Range { from, to } =>
+				(from.as_word()..=to.as_word()).contains(&word),
+		}
+	}
+}
+
+/// Source bit or range of source bits of field map, see `Area::field_map`.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Field {
+	/// One source bit as in `7`.
+	Bit(usize),
+	/// Inclusive range of source bits as in `{ from = 15, to = 8 }`, descending
+	/// if `from` is greater than `to`.
+	Range {
+		/// First source bit of range.
+		from: usize,
+		/// Last source bit of range.
+		to: usize,
+	},
+}
+
+impl Field {
+	fn bits(&self) -> Vec<usize> {
+		match *self {
+			Field::Bit(bit) => vec![bit],
+			Field::Range { from, to } if from <= to => (from..=to).collect(),
+			Field::Range { from, to } => (to..=from).rev().collect(),
+		}
+	}
+}
+
 /// TOML `u128` workaround.
-#[derive(Debug, Eq, PartialEq, Copy, Clone, Deserialize)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum Word {
 	/// One `u64` TOML integer as `u128`.
@@ -178,3 +1456,734 @@ impl Word {
 		}
 	}
 }
+
+impl From<u128> for Word {
+	/// One `u64` if it fits, otherwise two `[msb, lsb]`.
+	fn from(word: u128) -> Self {
+		if word <= u64::MAX as u128 {
+			Word::One(word as u64)
+		} else {
+			Word::Two([(word >> 64) as u64, word as u64])
+		}
+	}
+}
+
+/// Structure of `Mif` as serialized, see `Mif::serialize()`.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct Layout {
+	width: usize,
+	depth: usize,
+	runs: Vec<Run>,
+	areas: Vec<Source>,
+}
+
+/// Run of words as serialized.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct Run {
+	word: Word,
+	bulk: usize,
+}
+
+impl<T> Serialize for Mif<T>
+where
+	T: UpperHex + Unsigned + PrimInt + FromPrimitive + CheckedShl + WrappingSub,
+{
+	/// Serializes width, depth, runs of words with their bulk, and addresses
+	/// and paths of memory areas.
+	///
+	/// Words are serialized as `Word` to fit TOML integers.
+	fn serialize<S: Serializer>(&self, serializer: S)
+	-> Result<S::Ok, S::Error> {
+		Layout {
+			width: self.width(),
+			depth: self.depth(),
+			runs: self.words().iter().map(|&(word, bulk)| Run {
+				word: word.to_u128().unwrap_or_default().into(),
+				bulk,
+			}).collect(),
+			areas: self.areas().iter().map(|(addr, path)| Source {
+				addr: *addr,
+				path: path.clone(),
+			}).collect(),
+		}.serialize(serializer)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+	#[test]
+	fn dump_rejects_irrelevant_style() {
+		let dump = |format, style: &Style| dump(&mut Vec::new(),
+			&mut &[0u8; 4][..], 4, Width::Given(8), Order::Given(First::Lsb),
+			None, format, None, style).map(drop);
+		let lower = Style { case: HexCase::Lower, ..Style::default() };
+		let record = Style { record_bytes: Some(4), ..Style::default() };
+		assert!(dump(Format::Mif, &lower).is_ok());
+		assert!(dump(Format::Ihex, &lower).is_err());
+		assert!(dump(Format::Ihex, &record).is_ok());
+		assert!(dump(Format::Coe, &record).is_err());
+		assert!(dump(Format::Mif, &record).is_err());
+		let min_run = Style { min_run: Some(3), ..Style::default() };
+		for format in [Format::Mif, Format::Readmemh, Format::Vhdl] {
+			assert!(dump(format, &min_run).is_ok());
+		}
+		for format in [Format::Ihex, Format::Srec, Format::Coe, Format::C] {
+			assert!(dump(format, &min_run).is_err());
+		}
+	}
+
+	/// In-memory storage of binaries and of the MIFs created so far.
+	#[derive(Default)]
+	struct Memory {
+		bins: BTreeMap<PathBuf, Vec<u8>>,
+		mifs: Rc<RefCell<BTreeMap<PathBuf, Vec<u8>>>>,
+	}
+
+	/// MIF created by `Memory`.
+	struct Created(PathBuf, Rc<RefCell<BTreeMap<PathBuf, Vec<u8>>>>);
+
+	impl Write for Created {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			self.1.borrow_mut().entry(self.0.clone()).or_default()
+				.extend_from_slice(buf);
+			Ok(buf.len())
+		}
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	impl Memory {
+		fn new(bins: &[(&str, &[u8])]) -> Self {
+			let bins = bins.iter()
+				.map(|&(path, bytes)| (PathBuf::from(path), bytes.to_vec()))
+				.collect();
+			Memory { bins, ..Memory::default() }
+		}
+		fn mif(&self, path: &str) -> String {
+			String::from_utf8(self.mifs.borrow()[Path::new(path)].clone())
+				.unwrap()
+		}
+	}
+
+	impl Storage for Memory {
+		fn open(&mut self, path: &Path) -> Result<Box<dyn Read>> {
+			let bytes = self.bins.get(path).context("No such binary")?;
+			Ok(Box::new(Cursor::new(bytes.clone())))
+		}
+		fn create(&mut self, path: &Path) -> Result<Box<dyn Write>> {
+			self.mifs.borrow_mut().insert(path.to_path_buf(), Vec::new());
+			Ok(Box::new(Created(path.to_path_buf(), self.mifs.clone())))
+		}
+	}
+
+	/// Two binaries joining areas into the same MIF.
+	const AB: &str = r#"
+		[["b.bin"]]
+		width = 8
+		depth = 2
+		joins = ["ab.mif"]
+		[["a.bin"]]
+		width = 8
+		depth = 3
+		joins = ["ab.mif"]
+	"#;
+
+	fn ab() -> Memory {
+		Memory::new(&[("a.bin", &[0xaa; 3]), ("b.bin", &[0xb0, 0xb1])])
+	}
+
+	#[test]
+	fn join_is_deterministic() {
+		let files = toml::from_str::<Files>(AB).unwrap();
+		let style = Style { areas: true, ..Style::default() };
+		let golden = "\
+			-- 0: b.bin\n\
+			-- 2: a.bin\n\
+			\n\
+			WIDTH=8;\n\
+			DEPTH=5;\n\
+			\n\
+			ADDRESS_RADIX=HEX;\n\
+			DATA_RADIX=HEX;\n\
+			\n\
+			CONTENT BEGIN\n\
+			\t0  :   B0;\n\
+			\t1  :   B1;\n\
+			\t[2..4]  :   AA;\n\
+			END;\n";
+		for _ in 0..3 {
+			let mut memory = ab();
+			join_with(&files, &mut memory, &style, None).unwrap();
+			assert_eq!(memory.mif("ab.mif"), golden);
+		}
+		let mut sorted = files.clone();
+		sorted.sort_keys();
+		let mut memory = ab();
+		join_with(&sorted, &mut memory, &style, None).unwrap();
+		assert!(memory.mif("ab.mif").starts_with("-- 0: a.bin\n-- 3: b.bin\n"));
+	}
+
+	/// Temporary directory of `test` removed beforehand.
+	fn temp_dir(test: &str) -> PathBuf {
+		let dir = std::env::temp_dir()
+			.join(format!("mif-{}-{}", test, std::process::id()));
+		let _ = std::fs::remove_dir_all(&dir);
+		std::fs::create_dir_all(&dir).unwrap();
+		dir
+	}
+
+	#[test]
+	fn join_piped_toml_relative_to_toml_dir() {
+		let dir = temp_dir("toml-dir");
+		let toml_dir = dir.join("toml");
+		std::fs::create_dir_all(toml_dir.join("bins")).unwrap();
+		std::fs::create_dir_all(toml_dir.join("mifs")).unwrap();
+		std::fs::write(toml_dir.join("bins/a.bin"), [0xaa; 3]).unwrap();
+		std::fs::write(toml_dir.join("bins/b.bin"), [0xb0, 0xb1]).unwrap();
+		std::fs::write(toml_dir.join("b.toml"),
+			"[[\"b.bin\"]]\nwidth = 8\ndepth = 2\njoins = [\"ab.mif\"]\n")
+			.unwrap();
+		let piped = "\
+			include = [\"b.toml\"]\n\
+			[[\"a.bin\"]]\nwidth = 8\ndepth = 3\njoins = [\"ab.mif\"]\n";
+		assert!(format!("{:#}", load_from(&mut piped.as_bytes(), &dir)
+			.unwrap_err()).contains("b.toml"));
+		let (files, _mifs) = load_from(&mut piped.as_bytes(), &toml_dir)
+			.unwrap();
+		assert_eq!(files, toml::from_str::<Files>(AB).unwrap());
+		let style = Style::default();
+		assert!(join(&files, (&"bins", &"mifs"), &style, None).is_err());
+		let (bins, mifs) = (toml_dir.join("bins"), toml_dir.join("mifs"));
+		let manifest = join(&files, (&bins, &mifs), &style, None).unwrap();
+		assert_eq!(manifest[Path::new("ab.mif")].depth, 5);
+		let mut memory = ab();
+		join_with(&files, &mut memory, &style, None).unwrap();
+		assert_eq!(std::fs::read_to_string(toml_dir.join("mifs/ab.mif"))
+			.unwrap(), memory.mif("ab.mif"));
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn expand_tilde_and_variables() {
+		std::env::set_var("MIF_TEST_ROMS", "/opt/roms");
+		std::env::remove_var("MIF_TEST_UNSET");
+		let expand = |path: &str| expand(&path).map_err(|err| err.to_string());
+		assert_eq!(expand("$MIF_TEST_ROMS/a.bin").unwrap(),
+			Path::new("/opt/roms/a.bin"));
+		assert_eq!(expand("${MIF_TEST_ROMS}_old/a.bin").unwrap(),
+			Path::new("/opt/roms_old/a.bin"));
+		assert_eq!(expand("a~/b.bin").unwrap(), Path::new("a~/b.bin"));
+		assert_eq!(expand("~user/a.bin").unwrap(), Path::new("~user/a.bin"));
+		if let Ok(home) = var(HOME) {
+			assert_eq!(expand("~/a.bin").unwrap(),
+				PathBuf::from(home.clone() + "/a.bin"));
+			assert_eq!(expand("~").unwrap(), PathBuf::from(home));
+		}
+		assert!(expand("$MIF_TEST_UNSET/a.bin").unwrap_err()
+			.contains("Cannot expand `$MIF_TEST_UNSET`"));
+		assert!(expand("${MIF_TEST_ROMS/a.bin").unwrap_err()
+			.contains("Unclosed"));
+		assert!(expand("$/a.bin").unwrap_err().contains("Missing variable"));
+	}
+
+	/// `Memory` opening binaries as seekable `Cursor`s.
+	struct Seekable(Memory);
+
+	impl Storage for Seekable {
+		fn open(&mut self, _path: &Path) -> Result<Box<dyn Read>> {
+			unreachable!("Binary opened as non-seekable");
+		}
+		fn open_seekable(&mut self, path: &Path)
+		-> Result<Option<Box<dyn ReadSeek>>> {
+			let bytes = self.0.bins.get(path).context("No such binary")?;
+			Ok(Some(Box::new(Cursor::new(bytes.clone()))))
+		}
+		fn create(&mut self, path: &Path) -> Result<Box<dyn Write>> {
+			self.0.create(path)
+		}
+	}
+
+	#[test]
+	fn join_in_memory_seeking_past_skipped_areas() {
+		let files = toml::from_str::<Files>(r#"
+			[["c.bin"]]
+			width = 8
+			depth = 2
+			skips = []
+			[["c.bin"]]
+			width = 8
+			depth = 2
+			joins = ["c.mif"]
+		"#).unwrap();
+		let bytes = [0x01, 0x02, 0xc0, 0xc1];
+		let mut readable = Memory::new(&[("c.bin", &bytes)]);
+		join_with(&files, &mut readable, &Style::default(), None).unwrap();
+		let mut seekable = Seekable(Memory::new(&[("c.bin", &bytes)]));
+		join_with(&files, &mut seekable, &Style::default(), None).unwrap();
+		assert_eq!(seekable.0.mif("c.mif"), readable.mif("c.mif"));
+		assert!(readable.mif("c.mif").ends_with("\
+			\t0  :   C0;\n\
+			\t1  :   C1;\n\
+			END;\n"));
+		let mut seekable = Seekable(Memory::new(&[("c.bin", &bytes[..1])]));
+		assert!(join_with(&files, &mut seekable, &Style::default(), None)
+			.is_err());
+	}
+
+	#[test]
+	fn load_defaults_with_partial_overrides() {
+		let dir = temp_dir("load_defaults_with_partial_overrides");
+		let toml = dir.join("defaults.toml");
+		std::fs::write(&toml, r#"
+			[defaults]
+			first = "msb"
+			width = 24
+			[["a.bin"]]
+			depth = 1
+			joins = ["a.mif"]
+			[["a.bin"]]
+			width = 8
+			depth = 1
+			joins = ["a.mif"]
+			[["b.bin"]]
+			first = "lsb"
+			depth = 1
+			joins = ["b.mif"]
+		"#).unwrap();
+		let (files, _mifs) = load(&toml).unwrap();
+		let areas = |bin| files[Path::new(bin)].iter()
+			.map(|area| (area.first, area.width)).collect::<Vec<_>>();
+		assert_eq!(areas("a.bin"), [(First::Msb, 24), (First::Msb, 8)]);
+		assert_eq!(areas("b.bin"), [(First::Lsb, 24)]);
+		std::fs::write(&toml, "[defaults]\ndepth = 1\n").unwrap();
+		assert!(load(&toml).is_err());
+		std::fs::write(&toml, "[[\"a.bin\"]]\ndepth = 1\nskips = []\n")
+			.unwrap();
+		let (files, _mifs) = load(&toml).unwrap();
+		assert_eq!(files.values().flatten().next().unwrap().width, 16);
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn verify_declared_depth() {
+		let files = toml::from_str::<Files>(AB).unwrap();
+		let manifest = join_with(&files, &mut ab(), &Style::default(), None)
+			.unwrap();
+		let verify = |mifs| verify(&manifest, &toml::from_str(mifs).unwrap());
+		assert!(verify("[\"ab.mif\"]\ndepth = 5\n").is_ok());
+		assert!(verify("[\"ab.mif\"]\n").is_ok());
+		assert!(verify("[\"ab.mif\"]\ndepth = 4\n").is_err());
+		assert!(verify("[\"ba.mif\"]\ndepth = 5\n").is_err());
+	}
+
+	#[test]
+	fn sort_shuffled_areas_by_offset() {
+		let areas = |offsets: [usize; 3]| toml::from_str::<Files>(&format!(r#"
+			[["c.bin"]]
+			width = 8
+			depth = 2
+			offset = {}
+			joins = ["c.mif"]
+			[["c.bin"]]
+			width = 8
+			depth = 2
+			offset = {}
+			joins = ["c.mif"]
+			[["c.bin"]]
+			width = 8
+			depth = 2
+			offset = {}
+			joins = ["c.mif"]
+		"#, offsets[0], offsets[1], offsets[2])).unwrap();
+		fn join(files: &Files) -> Result<String> {
+			let mut memory = Memory::new(&[("c.bin", &[0, 1, 2, 3, 4, 5])]);
+			join_with(files, &mut memory, &Style::default(), None)
+				.map(|_manifest| memory.mif("c.mif"))
+		}
+		let mut files = areas([4, 0, 2]);
+		assert!(join(&files).is_err());
+		sort_areas(&mut files).unwrap();
+		assert_eq!(join(&files).unwrap(), join(&areas([0, 2, 4])).unwrap());
+		let mut files = areas([2, 0, 3]);
+		sort_areas(&mut files).unwrap();
+		assert!(join(&files).is_err());
+		let mut files = areas([0, 4, 2]);
+		files[0][1].at = None;
+		assert!(sort_areas(&mut files).is_err());
+	}
+
+	#[test]
+	fn skip_words_and_ranges() {
+		let files = toml::from_str::<Files>(r#"
+			[["s.bin"]]
+			width = 8
+			depth = 4
+			skips = [0x01, { from = 0x10, to = 0x1f }, 0xff]
+		"#).unwrap();
+		let area = &files[Path::new("s.bin")][0];
+		let read = |bytes: &[u8]| read_area(&mut Mif::new(8).unwrap(),
+			&mut &bytes[..], area);
+		assert_eq!(read(&[0x01, 0x10, 0x1f, 0xff]).unwrap(), 4);
+		assert_eq!(read(&[0x15, 0x15, 0x01, 0x01]).unwrap(), 4);
+		assert!(read(&[0x01, 0x0f, 0x10, 0x10]).is_err());
+		assert!(read(&[0x20, 0x10, 0x10, 0x10]).is_err());
+	}
+
+	#[test]
+	fn read_area_per_instruction() {
+		let area = |width, toml: &str| toml::from_str::<Files>(&format!(
+			"[[\"x.bin\"]]\nwidth = {}\n{}", width, toml)).unwrap()[0][0]
+			.clone();
+		let read = |toml, bytes: &[u8]| {
+			let mut mif = Mif::new(8).unwrap();
+			read_area(&mut mif, &mut &bytes[..], &area(8, toml))
+				.map(|count| (count, mif.words().clone()))
+		};
+		let bytes = [0x12, 0x12, 0x34, 0x00, 0x56];
+		assert_eq!(read("depth = 3\njoins = []\n", &bytes).unwrap(),
+			(3, vec![(0x12, 2), (0x34, 1)]));
+		assert_eq!(read("depth = 2\nrepeat = 2\njoins = []\n", &bytes)
+			.unwrap(), (2, vec![(0x12, 4)]));
+		assert!(read("depth = 2\nrepeat = 0\njoins = []\n", &bytes).is_err());
+		assert_eq!(read("depth = 2\nskips = []\n", &bytes).unwrap(),
+			(2, vec![(0x12, 2)]));
+		assert_eq!(read("depth = 2\nskips = [0x12]\n", &bytes).unwrap(),
+			(2, vec![(0x12, 2)]));
+		assert!(read("depth = 3\nskips = [0x12]\n", &bytes).is_err());
+		assert!(read("depth = 2\nrepeat = 2\nskips = []\n", &bytes).is_err());
+		assert_eq!(read("depth = 5\nuntil = 0x00\njoins = []\n", &bytes)
+			.unwrap(), (4, vec![(0x12, 2), (0x34, 1), (0x00, 1)]));
+		assert_eq!(read("depth = 1\n\
+			field-map = [{ from = 4, to = 7 }, { from = 3, to = 0 }]\n\
+			joins = []\n", &bytes[2..]).unwrap(), (1, vec![(0x23, 1)]));
+		let mut mif = Mif::new(4).unwrap();
+		let packed = area(4, "depth = 3\npacked = true\njoins = []\n");
+		assert_eq!(read_area(&mut mif, &mut &bytes[2..], &packed).unwrap(), 2);
+		assert_eq!(mif.words(), &vec![(0x4, 1), (0x3, 1), (0x0, 1)]);
+		assert!(read_area(&mut mif, &mut &bytes[..], &area(8, "depth = 1\n\
+			joins = []\n")).is_err());
+	}
+
+	#[test]
+	fn verify_declared_crc32() {
+		let files = toml::from_str::<Files>(AB).unwrap();
+		let mut memory = ab();
+		let manifest = join_with(&files, &mut memory, &Style::default(), None)
+			.unwrap();
+		let crc = crc32(0, memory.mif("ab.mif").as_bytes());
+		let verify = |crc| verify(&manifest, &toml::from_str(&format!(
+			"[\"ab.mif\"]\ndepth = 5\ncrc32 = {:#x}\n", crc)).unwrap());
+		verify(crc).unwrap();
+		let error = verify(crc ^ 1).unwrap_err().to_string();
+		assert_eq!(error, format!("CRC-32 {:#010x} of `ab.mif` instead of \
+			{:#010x}", crc, crc ^ 1));
+	}
+
+	#[test]
+	fn read_dump_of_known_bytes() {
+		let bytes = [0x34, 0x12, 0x34, 0x12, 0xcd, 0xab];
+		let mif = read_dump(&mut &bytes[..], 6, 16, First::Lsb, None).unwrap();
+		assert_eq!((mif.width(), mif.depth()), (16, 3));
+		assert_eq!(mif.words(), &vec![(0x1234, 2), (0xabcd, 1)]);
+		let mif = read_dump(&mut &bytes[..], 6, 12, First::Msb, None);
+		assert!(mif.is_err());
+		assert!(read_dump(&mut &bytes[..], 5, 16, First::Lsb, None).is_err());
+		assert!(read_dump(&mut &bytes[..4], 6, 16, First::Lsb, None).is_err());
+	}
+
+	#[test]
+	fn field_map_notation() {
+		let files = toml::from_str::<Files>(r#"
+			[["m.bin"]]
+			width = 12
+			depth = 1
+			field-map = [
+				{ from = 8, to = 11 }, 3, 2, 1, 0, { from = 15, to = 12 },
+			]
+			joins = ["m.mif"]
+		"#).unwrap();
+		let area = &files[Path::new("m.bin")][0];
+		let mut mif = Mif::new(12).unwrap();
+		read_area(&mut mif, &mut &[0x1e, 0x5a][..], area).unwrap();
+		assert_eq!(mif.words(), &vec![(0xa7a, 1)]);
+	}
+
+	#[test]
+	fn join_to_mif_and_hex_of_same_content() {
+		let files = toml::from_str::<Files>(r#"
+			[["r.bin"]]
+			width = 16
+			depth = 3
+			joins = ["rom.mif", "rom.hex", "rom.bin"]
+		"#).unwrap();
+		let bytes = [0x34, 0x12, 0x34, 0x12, 0xcd, 0xab];
+		let mut memory = Memory::new(&[("r.bin", &bytes)]);
+		let manifest = join_with(&files, &mut memory, &Style::default(), None)
+			.unwrap();
+		assert_eq!(manifest.len(), 3);
+		let mif = Mif::<u16>::parse(&memory.mif("rom.mif")).unwrap();
+		assert_eq!(mif.words(), &vec![(0x1234, 2), (0xabcd, 1)]);
+		assert_eq!(parse::ihex(&memory.mif("rom.hex")).unwrap(), bytes);
+		assert_eq!(memory.mifs.borrow()[Path::new("rom.bin")], bytes);
+	}
+
+	/// Gzip-compressed bytes `[0x12, 0x34, 0x12, 0x34, 0x56, 0x78]`.
+	#[cfg(feature = "flate")]
+	const GZ: [u8; 26] = [
+		0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0x13, 0x32,
+		0x11, 0x32, 0x09, 0xab, 0x00, 0x00, 0xf1, 0xcd, 0x30, 0xc0, 0x06, 0x00,
+		0x00, 0x00,
+	];
+
+	#[cfg(feature = "flate")]
+	#[test]
+	fn open_gzip_compressed() {
+		let dir = temp_dir("open_gzip_compressed");
+		for name in ["rom.bin.gz", "rom.BIN.GZ", "rom.bin"] {
+			let path = dir.join(name);
+			std::fs::write(&path, GZ).unwrap();
+			let (mut bytes, count) = open(&path, None).unwrap();
+			let mut read = Vec::new();
+			bytes.read_to_end(&mut read).unwrap();
+			assert_eq!(read, [0x12, 0x34, 0x12, 0x34, 0x56, 0x78]);
+			assert_eq!(count, 6);
+			let (mut bytes, count) = open_at(&path, 2, Some(2), None).unwrap();
+			let mif = read_dump(&mut bytes, count, 16, First::Msb, None)
+				.unwrap();
+			assert_eq!(mif.words(), &vec![(0x1234, 1)]);
+			assert!(open(&path, Some(5)).is_err());
+		}
+		let path = dir.join("raw.bin");
+		std::fs::write(&path, &GZ[1..]).unwrap();
+		assert_eq!(open(&path, None).unwrap().1, 25);
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn pad_to_complete_partial_word() {
+		let bytes = |bytes: &[u8]| Box::new(Cursor::new(bytes.to_vec()));
+		let (mut padded, count) =
+			pad_to(bytes(&[0x34, 0x12, 0x78]), 3, 16, 0xff);
+		assert_eq!(count, 4);
+		let mif = read_dump(&mut padded, count, 16, First::Lsb, None).unwrap();
+		assert_eq!(mif.words(), &vec![(0x1234, 1), (0xff78, 1)]);
+		let mut partial = bytes(&[0x34, 0x12, 0x78]);
+		assert!(read_dump(&mut partial, 3, 16, First::Lsb, None).is_err());
+		let (_padded, count) = pad_to(bytes(&[0x34, 0x12]), 2, 16, 0xff);
+		assert_eq!(count, 2);
+	}
+
+	#[test]
+	fn join_to_bin_in_order_of_areas() {
+		let areas = |second| toml::from_str::<Files>(&format!(r#"
+			[["r.bin"]]
+			first = "msb"
+			width = 16
+			depth = 1
+			joins = ["rom.mif", "rom.bin"]
+			[["r.bin"]]
+			first = "{}"
+			width = 16
+			depth = 1
+			joins = ["rom.mif", "rom.bin"]
+		"#, second)).unwrap();
+		let join = |files| {
+			let bytes = [0x12, 0x34, 0x56, 0x78];
+			let mut memory = Memory::new(&[("r.bin", &bytes)]);
+			join_with(&files, &mut memory, &Style::default(), None)
+				.map(|_manifest| memory)
+		};
+		let memory = join(areas("msb")).unwrap();
+		assert_eq!(memory.mifs.borrow()[Path::new("rom.bin")],
+			[0x12, 0x34, 0x56, 0x78]);
+		let mif = Mif::<u16>::parse(&memory.mif("rom.mif")).unwrap();
+		assert_eq!(mif.words(), &vec![(0x1234, 1), (0x5678, 1)]);
+		assert!(join(areas("lsb")).is_err());
+	}
+
+	#[test]
+	fn guess_width_of_crafted_bytes() {
+		assert_eq!(guess_width(&[], First::Lsb).unwrap(), 8);
+		assert_eq!(guess_width(&[0, 1, 2, 3, 4, 5], First::Lsb).unwrap(), 8);
+		let halves = [0x12, 0x34].repeat(4);
+		assert_eq!(guess_width(&halves, First::Lsb).unwrap(), 16);
+		let thirds = [0x12, 0x34, 0x56].repeat(4);
+		assert_eq!(guess_width(&thirds, First::Msb).unwrap(), 24);
+		// Fewest runs at 32 bits (2 runs of 4 words) but fewest runs per word
+		// at 8 bits (5 runs of 16 words) with 16 bits in between (3 of 8).
+		let mut tail = vec![0; 12];
+		tail.extend([1, 2, 3, 4]);
+		assert_eq!(guess_width(&tail, First::Lsb).unwrap(), 8);
+	}
+
+	#[test]
+	fn load_two_level_includes() {
+		let dir = temp_dir("load_two_level_includes");
+		std::fs::create_dir(dir.join("sub")).unwrap();
+		let top = dir.join("top.toml");
+		let write = |path, toml| std::fs::write(dir.join(path), toml).unwrap();
+		write("top.toml", r#"
+			include = ["sub/banks.toml"]
+			[["c.bin"]]
+			depth = 1
+			joins = ["c.mif"]
+		"#);
+		write("sub/banks.toml", r#"
+			include = ["common.toml"]
+			[defaults]
+			width = 8
+			[["b.bin"]]
+			depth = 1
+			joins = ["b.mif"]
+			[mifs."b.mif"]
+			depth = 1
+		"#);
+		write("sub/common.toml", r#"
+			[["a.bin"]]
+			depth = 1
+			joins = ["a.mif"]
+		"#);
+		let (files, mifs) = load(&top).unwrap();
+		let areas = files.iter()
+			.map(|(bin_path, areas)| (bin_path.to_str(), areas[0].width))
+			.collect::<Vec<_>>();
+		assert_eq!(areas, [
+			(Some("a.bin"), 16), (Some("b.bin"), 8), (Some("c.bin"), 16)]);
+		assert_eq!(mifs.keys().collect::<Vec<_>>(), [Path::new("b.mif")]);
+		let error = |path: &Path| format!("{:#}", load(&path).unwrap_err());
+		write("top.toml", r#"
+			include = ["sub/banks.toml"]
+			[["a.bin"]]
+			depth = 1
+			joins = ["a.mif"]
+		"#);
+		assert!(error(&top).ends_with("Binary `a.bin` loaded twice"));
+		write("sub/common.toml", r#"include = ["../top.toml"]"#);
+		assert!(error(&top).contains("Include cycle"));
+		assert!(error(&top).contains("top.toml` -> `"));
+		write("sub/common.toml", r#"include = ["missing.toml"]"#);
+		assert!(error(&top).contains("Cannot open `"));
+		assert!(error(&top).contains("missing.toml`"));
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn join_readme_example_noting_skipped_areas() {
+		let readme = include_str!("../README.md");
+		let example = &readme[readme.find("#### Join Example").unwrap()..];
+		let start = example.find("```toml\n").unwrap() + "```toml\n".len();
+		let end = start + example[start..].find("```").unwrap();
+		let toml = &example[start..end];
+		let dir = temp_dir("join_readme_example_noting_skipped_areas");
+		std::fs::write(dir.join("join.toml"), toml).unwrap();
+		let (files, _mifs) = load(&dir.join("join.toml")).unwrap();
+		let rom = |prog, skip, data: [u8; 2]| [
+			[0x56, 0x34, 0x12].repeat(prog),
+			[0xff; 3].repeat(skip),
+			data.repeat(1024),
+		].concat();
+		let a_rom = rom(1267, 781, [0xab, 0xcd]);
+		let b_rom = rom(1747, 301, [0x34, 0x12]);
+		let mut memory = Memory::new(&[("a.rom", &a_rom), ("b.rom", &b_rom)]);
+		let style = Style { areas: true, sources: true, ..Style::default() };
+		join_with(&files, &mut memory, &style, None).unwrap();
+		assert_eq!(memory.mif("ab.data.mif"), "\
+			-- 000: skipped 781 words of FFFFFF in a.rom\n\
+			-- 000: a.rom\n\
+			-- 400: skipped 301 words of FFFFFF in b.rom\n\
+			-- 400: b.rom\n\
+			\n\
+			WIDTH=16;\n\
+			DEPTH=2048;\n\
+			\n\
+			ADDRESS_RADIX=HEX;\n\
+			DATA_RADIX=HEX;\n\
+			\n\
+			CONTENT BEGIN\n\
+			\t[000..3FF]  :   ABCD; -- from a.rom\n\
+			\t[400..7FF]  :   1234; -- from b.rom\n\
+			END;\n");
+		assert!(memory.mif("b.data.mif").starts_with("\
+			-- 000: skipped 301 words of FFFFFF in b.rom\n\
+			-- 000: b.rom\n\
+			\n"));
+		assert!(!memory.mif("ab.prog.mif").contains("skipped"));
+		let style = Style { areas: true, ..Style::default() };
+		join_with(&files, &mut memory, &style, None).unwrap();
+		assert!(memory.mif("ab.data.mif").starts_with("\
+			-- 000: a.rom\n\
+			-- 400: b.rom\n\
+			\n"));
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn read_in_up_to_unbounded_length() {
+		let bytes = [0, 1, 2, 3, 4, 5];
+		for max in [None, Some(usize::MAX)] {
+			let read = read_in(&mut &bytes[..], 2, max, max).unwrap();
+			assert_eq!(read, (vec![2, 3, 4, 5], 2));
+		}
+		let read = read_in(&mut &bytes[..], 1, Some(2), Some(usize::MAX));
+		assert_eq!(read.unwrap(), (vec![1, 2, 3], 1));
+		let read = read_in(&mut &bytes[..], 8, Some(usize::MAX), None);
+		assert_eq!(read.unwrap(), (vec![], 6));
+	}
+
+	#[test]
+	fn serialize_areas_round_trip() {
+		let area = Area {
+			first: First::Msb,
+			width: 16,
+			depth: 4,
+			until: Some(Word::One(0xffff)),
+			packed: false,
+			field_map: Some(vec![
+				Field::Range { from: 8, to: 15 },
+				Field::Bit(0),
+				Field::Range { from: 7, to: 1 },
+			]),
+			repeat: Some(3),
+			name: Some("prog".into()),
+			at: Some(0),
+			input: Input::Bin,
+			instr: Instr::Joins(vec!["a.mif".into(), "a.hex".into()]),
+		};
+		let skips = Area {
+			first: First::Lsb,
+			width: 128,
+			depth: 2,
+			until: None,
+			packed: true,
+			field_map: None,
+			repeat: None,
+			name: None,
+			at: Some(8),
+			input: Input::Srec,
+			instr: Instr::Skips(vec![
+				Skip::Word(Word::One(0xff)),
+				Skip::Word(Word::Two([0x1, 0x2])),
+				Skip::Range { from: Word::One(0x0), to: Word::Two([0x1, 0x0]) },
+			]),
+		};
+		let mut files = Files::new();
+		files.insert("a.bin".into(), vec![area.clone(), skips.clone()]);
+		files.insert("b.bin".into(), vec![Area {
+			until: Some(Word::Two([0x1, 0x2])),
+			..area
+		}]);
+		let toml = to_toml(&files).unwrap();
+		assert!(toml.contains("\
+			field-map = [{ from = 8, to = 15 }, 0, { from = 7, to = 1 }]\n\
+			repeat = 3\n"));
+		assert!(toml.contains("\
+			skips = [255, [1, 2], { from = 0, to = [1, 0] }]\n"));
+		assert_eq!(toml::from_str::<Files>(&toml).unwrap(), files);
+	}
+}
@@ -0,0 +1,504 @@
+use std::{
+	io::{self, Write},
+	convert::TryFrom,
+	fmt::UpperHex,
+};
+use num_traits::{
+	sign::Unsigned, int::PrimInt, cast::FromPrimitive,
+	ops::{checked::CheckedShl, wrapping::WrappingSub},
+};
+use crate::{Mif, First::{self, Msb}, Format, Style, HexCase, Hex, Result};
+use crate::Error::*;
+
+impl<T> Mif<T>
+where
+	T: UpperHex + Unsigned + PrimInt + FromPrimitive + CheckedShl + WrappingSub,
+{
+	/// Writes MIF in given `format` to writer.
+	///
+	///   * `lines`: Writer, MIF is written to.
+	///   * `format`: Output format, see `Format`.
+	///   * `first`: LSB/MSB first (little/big-endian) of byte-oriented formats.
+	///   * `style`: How to write MIF, see `Style`.
+	pub fn write_as(
+		&self,
+		lines: &mut dyn Write,
+		format: Format,
+		first: First,
+		style: &Style,
+	) -> Result<()> {
+		match format {
+			Format::Mif => self.write(lines, style),
+			Format::Ihex => self.ihex(lines, first, style.record_bytes),
+			Format::Srec => self.srec(lines, first, style.record_bytes),
+			Format::Coe => self.coe(lines, style.case),
+			Format::Readmemh => self.readmemh(lines, style.case, style.min_run),
+			Format::Vhdl => self.vhdl(lines, style),
+			Format::C => self.c_header(lines, "mif", None, style.case),
+		}
+	}
+	/// MIF in given `format` as string, see `write_as()`.
+	pub fn to_string_as(&self, format: Format, first: First, style: &Style)
+	-> Result<String> {
+		let mut lines = Vec::new();
+		self.write_as(&mut lines, format, first, style)?;
+		Ok(String::from_utf8(lines).unwrap())
+	}
+	/// Writes LSB/MSB-`first` words as raw bytes to writer.
+	///
+	/// Each word occupies `align()` bytes. This is the inverse of `read()`.
+	pub fn write_bytes(&self, bytes: &mut dyn Write, first: First)
+	-> Result<()> {
+		bytes.write_all(&self.bytes(first).collect::<Vec<u8>>())?;
+		Ok(())
+	}
+	/// Writes LSB/MSB-`first` words as Intel HEX to writer.
+	///
+	/// Emits 16 data bytes per record, see `Style::record_bytes` otherwise,
+	/// and extended linear address records whenever the byte address exceeds
+	/// 16 bits.
+	pub fn write_ihex(&self, lines: &mut dyn Write, first: First)
+	-> Result<()> {
+		self.ihex(lines, first, None)
+	}
+	/// Writes Intel HEX of optional `record` bytes, see `write_ihex()`.
+	///
+	/// Records are split at 64 KiB boundaries so none wraps its 16-bit address.
+	fn ihex(&self, lines: &mut dyn Write, first: First, record: Option<usize>)
+	-> Result<()> {
+		let record = record_bytes(record, 255)?;
+		let bytes = self.bytes(first).collect::<Vec<u8>>();
+		if u32::try_from(bytes.len().saturating_sub(1)).is_err() {
+			Err(AddressOutOfRange(bytes.len() - 1))?;
+		}
+		let mut base = 0;
+		let mut addr = 0;
+		while addr < bytes.len() {
+			if addr >> 16 != base {
+				base = addr >> 16;
+				ihex_record(lines, 0, 4, &(base as u16).to_be_bytes())?;
+			}
+			let end = (addr + record).min(bytes.len()).min((base + 1) << 16);
+			ihex_record(lines, addr as u16, 0, &bytes[addr..end])?;
+			addr = end;
+		}
+		ihex_record(lines, 0, 1, &[])?;
+		Ok(())
+	}
+	/// Writes LSB/MSB-`first` words as Motorola S-record to writer.
+	///
+	/// Emits 16 data bytes per record, see `Style::record_bytes` otherwise,
+	/// using the shortest sufficient address size, that is `S1`/`S9`,
+	/// `S2`/`S8`, or `S3`/`S7` records.
+	pub fn write_srec(&self, lines: &mut dyn Write, first: First)
+	-> Result<()> {
+		self.srec(lines, first, None)
+	}
+	/// Writes S-record of optional `record` bytes, see `write_srec()`.
+	fn srec(&self, lines: &mut dyn Write, first: First, record: Option<usize>)
+	-> Result<()> {
+		let bytes = self.bytes(first).collect::<Vec<u8>>();
+		let last = bytes.len().saturating_sub(1);
+		let (data, stop, size) = match last {
+			0..=0xffff => (1, 9, 2),
+			0x1_0000..=0xff_ffff => (2, 8, 3),
+			_ if u32::try_from(last).is_ok() => (3, 7, 4),
+			_ => Err(AddressOutOfRange(last))?,
+		};
+		let record = record_bytes(record, 255 - size - 1)?;
+		srec_record(lines, 0, 0, 2, &[])?;
+		for (index, chunk) in bytes.chunks(record).enumerate() {
+			let addr = (index * record) as u32;
+			srec_record(lines, data, addr, size, chunk)?;
+		}
+		srec_record(lines, stop, 0, size, &[])?;
+		Ok(())
+	}
+	/// Writes words as Xilinx coefficient file to writer.
+	///
+	/// Writes uppercase hexadecimal digits, see `Style::case` otherwise.
+	pub fn write_coe(&self, lines: &mut dyn Write) -> Result<()> {
+		self.coe(lines, HexCase::Upper)
+	}
+	/// Writes COE of hexadecimal digits in `case`, see `write_coe()`.
+	fn coe(&self, lines: &mut dyn Write, case: HexCase) -> Result<()> {
+		let word_pads = self.word_pads();
+		writeln!(lines, "\
+			memory_initialization_radix=16;\n\
+			memory_initialization_vector=")?;
+		let mut words = self.expand().peekable();
+		while let Some(word) = words.next() {
+			let stop = if words.peek().is_some() { ',' } else { ';' };
+			let word = Hex(word.to_u128().unwrap(), word_pads, case);
+			writeln!(lines, "{}{}", word, stop)?;
+		}
+		Ok(())
+	}
+	/// Writes words as Verilog `$readmemh()` file to writer.
+	///
+	/// Writes uppercase hexadecimal digits, see `Style::case` otherwise.
+	pub fn write_readmemh(&self, lines: &mut dyn Write) -> Result<()> {
+		self.readmemh(lines, HexCase::Upper, None)
+	}
+	/// Writes `$readmemh()` file of hexadecimal digits in `case` marking runs
+	/// of at least `min_run` words by their address as in `@10` if given, see
+	/// `write_readmemh()` and `Style::min_run`.
+	fn readmemh(&self, lines: &mut dyn Write, case: HexCase,
+		min_run: Option<usize>) -> Result<()> {
+		let word_pads = self.word_pads();
+		let mut addr = 0;
+		for (word, bulk) in self.runs(Some(min_run.unwrap_or(usize::MAX))) {
+			if bulk > 1 {
+				writeln!(lines, "@{}", Hex(addr as u128, 1, case))?;
+			}
+			let word = Hex(word.to_u128().unwrap(), word_pads, case);
+			for _ in 0..bulk {
+				writeln!(lines, "{}", word)?;
+			}
+			addr += bulk;
+		}
+		Ok(())
+	}
+	/// Writes words as VHDL constant array of type `mif_type` to writer.
+	///
+	/// Words are written as hexadecimal bit strings if `width()` is a multiple
+	/// of 4, otherwise as binary bit strings. Writes uppercase hexadecimal
+	/// digits and ranges of at least two words, see `Style::case` and
+	/// `Style::min_run` otherwise. Errors on empty MIFs as VHDL arrays cannot
+	/// be empty.
+	pub fn write_vhdl(&self, lines: &mut dyn Write) -> Result<()> {
+		self.vhdl(lines, &Style::default())
+	}
+	/// Writes VHDL as of `Style::case` and `Style::min_run`, see
+	/// `write_vhdl()`.
+	fn vhdl(&self, lines: &mut dyn Write, style: &Style) -> Result<()> {
+		if self.depth == 0 {
+			Err(EmptyDepth("VHDL"))?;
+		}
+		writeln!(lines, "\
+			type mif_type is array (0 to {}) of \
+			std_logic_vector({} downto 0);\n\
+			constant mif : mif_type := (",
+			self.depth - 1, self.width - 1)?;
+		let mut addr = 0;
+		let mut runs = self.runs(style.min_run).peekable();
+		while let Some((word, bulk)) = runs.next() {
+			write!(lines, "\t")?;
+			if bulk == 1 {
+				write!(lines, "{}", addr)?;
+			} else {
+				write!(lines, "{} to {}", addr, addr + bulk - 1)?;
+			}
+			if self.width == self.word_pads() * 4 {
+				let word = word.to_u128().unwrap();
+				write!(lines, " => x\"{}\"",
+					Hex(word, self.word_pads(), style.case))?;
+			} else {
+				write!(lines, " => \"{:01$b}\"",
+					word.to_u128().unwrap(), self.width)?;
+			}
+			let comma = if runs.peek().is_some() { "," } else { "" };
+			writeln!(lines, "{}", comma)?;
+			addr += bulk;
+		}
+		writeln!(lines, ");")?;
+		Ok(())
+	}
+	/// Writes words as C array `name` of `ctype` elements to writer.
+	///
+	/// Defines the depth as `<NAME>_LEN` and writes runs expanded as
+	/// hexadecimal values. The element type defaults to the narrowest of
+	/// `uint8_t`, `uint16_t`, `uint32_t`, and `uint64_t` the width fits in,
+	/// erroring on wider words unless `ctype` is given. Writes uppercase
+	/// hexadecimal digits, see `Style::case` otherwise. Errors if `name` is no
+	/// C identifier and on empty MIFs as C arrays cannot be empty.
+	pub fn write_c_header(
+		&self,
+		lines: &mut dyn Write,
+		name: &str,
+		ctype: Option<&str>,
+	) -> Result<()> {
+		self.c_header(lines, name, ctype, HexCase::Upper)
+	}
+	/// Writes C header of hexadecimal digits in `case`, see
+	/// `write_c_header()`.
+	fn c_header(
+		&self,
+		lines: &mut dyn Write,
+		name: &str,
+		ctype: Option<&str>,
+		case: HexCase,
+	) -> Result<()> {
+		let ident = |char: char| char.is_ascii_alphanumeric() || char == '_';
+		let digit = |char: char| char.is_ascii_digit();
+		if name.is_empty() || name.starts_with(digit)
+			|| !name.chars().all(ident) {
+			Err(InvalidIdentifier(name.into()))?;
+		}
+		if self.depth == 0 {
+			Err(EmptyDepth("C array"))?;
+		}
+		let ctype = match ctype {
+			Some(ctype) => ctype,
+			None => match self.width {
+				0..=8 => "uint8_t",
+				9..=16 => "uint16_t",
+				17..=32 => "uint32_t",
+				33..=64 => "uint64_t",
+				width => Err(WidthOutOfCType(width))?,
+			},
+		};
+		let len = format!("{}_LEN", name.to_ascii_uppercase());
+		writeln!(lines, "\
+			#include <stdint.h>\n\n\
+			#define {} {}\n\n\
+			static const {} {}[{}] = {{", len, self.depth, ctype, name, len)?;
+		let word_pads = self.word_pads();
+		let mut words = self.expand().peekable();
+		while let Some(word) = words.next() {
+			let stop = if words.peek().is_some() { "," } else { "" };
+			let word = Hex(word.to_u128().unwrap(), word_pads, case);
+			writeln!(lines, "\t0x{}{}", word, stop)?;
+		}
+		writeln!(lines, "}};")?;
+		Ok(())
+	}
+	/// Bytes of LSB/MSB-`first` words one by one with their bulk expanded.
+	fn bytes(&self, first: First) -> impl Iterator<Item = u8> + '_ {
+		let first = first.resolve();
+		let align = self.align();
+		self.expand().flat_map(move |word| {
+			let mut bytes = word.to_u128().unwrap().to_le_bytes()[..align]
+				.to_vec();
+			if first == Msb {
+				bytes.reverse();
+			}
+			bytes
+		})
+	}
+}
+
+/// Data bytes per `record` of at most `max` bytes, 16 by default.
+fn record_bytes(record: Option<usize>, max: usize) -> Result<usize> {
+	let record = record.unwrap_or(16);
+	if (1..=max).contains(&record) {
+		Ok(record)
+	} else {
+		Err(RecordOutOfRange(record, max))
+	}
+}
+
+/// Writes Intel HEX record of `kind` with `data` at `addr`.
+fn ihex_record(lines: &mut dyn Write, addr: u16, kind: u8, data: &[u8])
+-> io::Result<()> {
+	write!(lines, ":{:02X}{:04X}{:02X}", data.len(), addr, kind)?;
+	let mut sum = (data.len() as u8)
+		.wrapping_add((addr >> 8) as u8).wrapping_add(addr as u8)
+		.wrapping_add(kind);
+	for &byte in data {
+		sum = sum.wrapping_add(byte);
+		write!(lines, "{:02X}", byte)?;
+	}
+	writeln!(lines, "{:02X}", sum.wrapping_neg())
+}
+
+/// Writes S-record of `kind` with `data` at `addr` of `size` bytes.
+fn srec_record(
+	lines: &mut dyn Write,
+	kind: u8,
+	addr: u32,
+	size: usize,
+	data: &[u8],
+) -> io::Result<()> {
+	let count = (size + data.len() + 1) as u8;
+	write!(lines, "S{}{:02X}", kind, count)?;
+	let mut sum = count;
+	for &byte in addr.to_be_bytes()[4 - size..].iter().chain(data) {
+		sum = sum.wrapping_add(byte);
+		write!(lines, "{:02X}", byte)?;
+	}
+	writeln!(lines, "{:02X}", !sum)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn text_formats_honor_case() {
+		let mut mif = Mif::<u16>::new(16).unwrap();
+		mif.push(0xabcd, 2).unwrap();
+		let lower = Style { case: HexCase::Lower, ..Style::default() };
+		for format in [Format::Coe, Format::Readmemh, Format::Vhdl, Format::C] {
+			let upper = mif.to_string_as(format, First::Lsb, &Style::default())
+				.unwrap();
+			let lower = mif.to_string_as(format, First::Lsb, &lower).unwrap();
+			assert!(upper.contains("ABCD") && !upper.contains("abcd"));
+			assert!(lower.contains("abcd") && !lower.contains("ABCD"));
+		}
+	}
+
+	#[test]
+	fn parse_then_write_bytes_in_either_order() {
+		let mif = Mif::<u32>::parse("WIDTH=24;\nDEPTH=2;\n\
+			ADDRESS_RADIX=HEX;\nDATA_RADIX=HEX;\n\
+			CONTENT BEGIN\n0 : 123456 ABCDEF;\nEND;\n").unwrap();
+		let (mut lsb, mut msb) = (Vec::new(), Vec::new());
+		mif.write_bytes(&mut lsb, First::Lsb).unwrap();
+		mif.write_bytes(&mut msb, Msb).unwrap();
+		assert_eq!(lsb, [0x56, 0x34, 0x12, 0xef, 0xcd, 0xab]);
+		assert_eq!(msb, [0x12, 0x34, 0x56, 0xab, 0xcd, 0xef]);
+	}
+
+	#[test]
+	fn empty_vhdl() {
+		let mut mif = Mif::<u8>::new(6).unwrap();
+		assert!(matches!(mif.write_vhdl(&mut Vec::new()),
+			Err(EmptyDepth("VHDL"))));
+		mif.push(0x2a, 1).unwrap();
+		assert_eq!(mif.to_string_as(Format::Vhdl, Msb, &Style::default())
+			.unwrap(), "\
+			type mif_type is array (0 to 0) of std_logic_vector(5 downto 0);\n\
+			constant mif : mif_type := (\n\
+			\t0 => \"101010\"\n\
+			);\n");
+	}
+
+	#[test]
+	fn record_bytes_at_bounds() {
+		let record = |bytes| Style {
+			record_bytes: Some(bytes),
+			..Style::default()
+		};
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		mif.push_slice(&[0xaa, 0xbb, 0xcc]).unwrap();
+		assert_eq!(mif.to_string_as(Format::Ihex, Msb, &record(1)).unwrap(), "\
+			:01000000AA55\n\
+			:01000100BB43\n\
+			:01000200CC31\n\
+			:00000001FF\n");
+		assert_eq!(mif.to_string_as(Format::Srec, Msb, &record(1)).unwrap(), "\
+			S0030000FC\n\
+			S1040000AA51\n\
+			S1040001BB3F\n\
+			S1040002CC2D\n\
+			S9030000FC\n");
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		mif.push_slice(&(0..300).map(|byte| byte as u8).collect::<Vec<_>>())
+			.unwrap();
+		let bytes = mif.bytes(Msb).collect::<Vec<_>>();
+		let ihex = mif.to_string_as(Format::Ihex, Msb, &record(255)).unwrap();
+		let lines = ihex.lines().collect::<Vec<_>>();
+		assert_eq!(lines.len(), 3);
+		assert!(lines[0].starts_with(":FF000000"));
+		assert!(lines[1].starts_with(":2D00FF00"));
+		assert_eq!(crate::parse::ihex(&ihex).unwrap(), bytes);
+		let srec = mif.to_string_as(Format::Srec, Msb, &record(252)).unwrap();
+		let lines = srec.lines().collect::<Vec<_>>();
+		assert_eq!(lines.len(), 4);
+		assert!(lines[1].starts_with("S1FF0000"));
+		assert!(lines[2].starts_with("S13300FC"));
+		assert_eq!(crate::parse::srec(&srec).unwrap(), bytes);
+		for (format, bytes, max) in [
+			(Format::Ihex, 0, 255),
+			(Format::Ihex, 256, 255),
+			(Format::Srec, 0, 252),
+			(Format::Srec, 253, 252),
+		] {
+			assert!(matches!(mif.to_string_as(format, Msb, &record(bytes)),
+				Err(RecordOutOfRange(record, limit))
+					if record == bytes && limit == max));
+		}
+	}
+
+	#[test]
+	fn min_run_of_all_formats() {
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		mif.push(0x01, 2).unwrap();
+		mif.push(0x02, 3).unwrap();
+		mif.push(0x03, 1).unwrap();
+		let style = |min_run| Style { min_run, ..Style::default() };
+		let to_string = |format, min_run|
+			mif.to_string_as(format, Msb, &style(min_run)).unwrap();
+		for format in [Format::Mif, Format::Vhdl] {
+			assert_eq!(to_string(format, None), to_string(format, Some(2)));
+			assert_eq!(to_string(format, Some(1)), to_string(format, Some(2)));
+		}
+		assert_eq!(to_string(Format::Mif, Some(3)), "\
+			WIDTH=8;\n\
+			DEPTH=6;\n\
+			\n\
+			ADDRESS_RADIX=HEX;\n\
+			DATA_RADIX=HEX;\n\
+			\n\
+			CONTENT BEGIN\n\
+			\t0  :   01;\n\
+			\t1  :   01;\n\
+			\t[2..4]  :   02;\n\
+			\t5  :   03;\n\
+			END;\n");
+		assert_eq!(Mif::<u8>::parse(&to_string(Format::Mif, Some(3))).unwrap()
+			.words(), mif.words());
+		assert_eq!(to_string(Format::Vhdl, Some(3)), "\
+			type mif_type is array (0 to 5) of std_logic_vector(7 downto 0);\n\
+			constant mif : mif_type := (\n\
+			\t0 => x\"01\",\n\
+			\t1 => x\"01\",\n\
+			\t2 to 4 => x\"02\",\n\
+			\t5 => x\"03\"\n\
+			);\n");
+		assert_eq!(to_string(Format::Vhdl, Some(4)).matches(" to ").count(), 1);
+		assert_eq!(to_string(Format::Mif, Some(4)).matches("..").count(), 0);
+		assert_eq!(to_string(Format::Readmemh, None), "\
+			01\n01\n02\n02\n02\n03\n");
+		assert_eq!(to_string(Format::Readmemh, Some(2)), "\
+			@0\n01\n01\n@2\n02\n02\n02\n03\n");
+		assert_eq!(to_string(Format::Readmemh, Some(3)), "\
+			01\n01\n@2\n02\n02\n02\n03\n");
+		assert_eq!(to_string(Format::Readmemh, Some(4)),
+			to_string(Format::Readmemh, None));
+		for format in [Format::Ihex, Format::Srec, Format::Coe, Format::C] {
+			assert_eq!(to_string(format, Some(3)), to_string(format, None));
+		}
+	}
+
+	#[test]
+	fn c_header_element_types_names_and_depth() {
+		let header = |width, name, ctype| {
+			let mut mif = Mif::<u128>::new(width).unwrap();
+			mif.push_word(1).unwrap();
+			let mut lines = Vec::new();
+			mif.write_c_header(&mut lines, name, ctype)
+				.map(|()| String::from_utf8(lines).unwrap())
+		};
+		for (widths, ctype) in [
+			(1..=8, "uint8_t"),
+			(9..=16, "uint16_t"),
+			(17..=32, "uint32_t"),
+			(33..=64, "uint64_t"),
+		] {
+			for width in widths {
+				let decl = format!("static const {} rom[ROM_LEN]", ctype);
+				assert!(header(width, "rom", None).unwrap().contains(&decl));
+			}
+		}
+		assert!(matches!(header(65, "rom", None), Err(WidthOutOfCType(65))));
+		assert_eq!(header(65, "rom_1", Some("unsigned __int128")).unwrap(), "\
+			#include <stdint.h>\n\
+			\n\
+			#define ROM_1_LEN 1\n\
+			\n\
+			static const unsigned __int128 rom_1[ROM_1_LEN] = {\n\
+			\t0x00000000000000001\n\
+			};\n");
+		assert!(header(8, "_Rom", None).is_ok());
+		for name in ["", "1rom", "rom-1", "rom 1", "röm"] {
+			assert!(matches!(header(8, name, None),
+				Err(InvalidIdentifier(invalid)) if invalid == name));
+		}
+		let mut lines = Vec::new();
+		assert!(matches!(Mif::<u8>::new(8).unwrap()
+			.write_c_header(&mut lines, "rom", None), Err(EmptyDepth(_))));
+		assert!(lines.is_empty());
+	}
+}
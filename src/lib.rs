@@ -6,10 +6,25 @@
 //!   * Detects single-word sequence `[first..last]: word` but does **not**
 //!     detect multi-word sequence `[first..last]: words..` in binary data.
 //!   * Verifies word fits into MIF's word width in bits.
+//!   * Reads words either aligned to bytes or bit-packed back-to-back.
 //!   * Joins multiple MIFs of different word widths as long as words fit.
 //!   * Optionally comments join offsets in words with given (file) names.
+//!   * Optionally comments runs with the (file) names they originate from.
+//!   * Optionally comments CRC-32 of every block of words to locate corruption.
+//!   * Computes and patches trailing checksums of ROM footers.
+//!   * Writes Intel HEX, S-record, COE, `$readmemh()`, VHDL, and C formats as
+//!     well.
+//!   * Writes Quartus, generic, or Gowin dialect of MIF, see `mif dump --dialect`.
+//!   * Optionally concatenates parity or Hamming code check bits to words.
+//!   * Parses MIF text of any address and data radix including signed decimal.
+//!   * Tokenizes MIF text for custom line-by-line transforms.
+//!   * Parses Intel HEX and S-record as bytes verifying their checksums.
+//!   * Packs null-terminated or length-prefixed string tables into words.
 //!   * Provides simple `mif dump` subcommand.
-//!   * Provides reproducible `mif join` subcommand via TOML instruction file.
+//!   * Provides simple `mif info` subcommand.
+//!   * Provides simple `mif convert` subcommand between formats.
+//!   * Provides reproducible `mif join` subcommand via TOML instruction file,
+//!     joining memory areas in given order or optionally sorted by binary paths.
 //!
 //! # Library
 //!
@@ -24,19 +39,43 @@
 //!
 //! Default features:
 //!
-//!   * `cli`: Provides command-line interface functionality of `mif` binary.
+//!   * `cli`: Provides command-line interface functionality of `mif` binary
+//!     and serialization of `Mif` and of join specs via `serde`.
 //!
 //!     Requires: `anyhow`, `indexmap`, `serde`, `toml`
 //!
 //!   * `bin`: Enables compilation of `mif` binary.
 //!
-//!     Requires: `cli`, `clap`
+//!     Requires: `cli`, `clap`, `serde_json`
+//!
+//! Optional features:
+//!
+//!   * `parallel`: Writes joined MIFs in parallel, see `mif join --jobs`.
+//!
+//!     Requires: `cli`, `rayon`
+//!
+//!   * `mmap`: Maps input files into memory instead of buffering them.
+//!
+//!     Requires: `cli`, `memmap2`
+//!
+//!   * `trace`: Traces progress and timing of joining binaries and their memory
+//!     areas. The `mif` binary prints it to standard error as filtered by the
+//!     `RUST_LOG` environment variable as in `RUST_LOG=mif=info mif join`.
+//!
+//!     Requires: `cli`, `tracing`, `tracing-subscriber`
+//!
+//!   * `flate`: Decompresses gzip-compressed inputs detected by their `.gz`
+//!     extension or their magic bytes, see `cli::open_at()`.
+//!
+//!     Requires: `cli`, `flate2`
 //!
 //! # Command-line Interface
 //!
 //! Install via `cargo install mif`.
 //!
-//! Provides two subcommands, `dump` and `join`.
+//! Provides four subcommands, `dump`, `info`, `join`, and `convert`. Errors are
+//! printed as one JSON object of `kind`, `file`, `address`, `message`, and
+//! `causes` with `mif --format-errors json` for editor and CI integration.
 //!
 //! ```text
 //! mif 0.3.0
@@ -44,16 +83,25 @@
 //! Memory Initialization File
 //!
 //! USAGE:
-//!     mif <SUBCOMMAND>
+//!     mif [OPTIONS] <SUBCOMMAND>
 //!
 //! OPTIONS:
-//!     -h, --help       Prints help information
-//!     -V, --version    Prints version information
+//!         --format-errors <human|json>
+//!             Error output: human or json [default: human]
+//!
+//!     -q, --quiet                         Prints no warnings, errors only
+//!     -v, --verbose
+//!             Prints progress and summary to standard error
+//!
+//!     -h, --help                          Prints help information
+//!     -V, --version                       Prints version information
 //!
 //! SUBCOMMANDS:
-//!     dump    Dumps binary as MIF
-//!     join    Joins binaries' memory areas to MIFs
-//!     help    Prints this message or the help of the given subcommand(s)
+//!     dump       Dumps binary as MIF
+//!     info       Prints binary's MIF information
+//!     join       Joins binaries' memory areas to MIFs
+//!     convert    Converts between formats
+//!     help       Prints this message or the help of the given subcommand(s)
 //! ```
 //!
 //! ## Dump Subcommand
@@ -63,16 +111,134 @@
 //! Dumps binary as MIF
 //!
 //! USAGE:
-//!     mif dump [input]
+//!     mif dump [OPTIONS] [inputs]...
+//!
+//! ARGS:
+//!     <inputs>...    Input files concatenated or standard input (-) [default:
+//!                    -]
+//!
+//! OPTIONS:
+//!     -w, --width <bits|auto>
+//!             Word width in bits from 1 to 128 or auto guessing 8, 16, 24, or 32
+//!             [default: 16]
+//!
+//!     -f, --first <lsb|msb|native|auto>
+//!             LSB/MSB first (little/big-endian), native, or auto [default: lsb]
+//!
+//!         --byte-group <bytes>
+//!             Bytes per group read LSB/MSB first of mixed-endian words, say 2 for
+//!             32-bit words of two 16-bit halves
+//!
+//!         --group-first <lsb|msb|native>
+//!             LSB/MSB first (little/big-endian) or native of byte groups [default:
+//!             msb]
+//!
+//!     -F, --format <format>
+//!             Output format: mif, ihex, srec, coe, readmemh, vhdl, or c [default:
+//!             mif]
+//!
+//!         --record-bytes <bytes>
+//!             Data bytes per record from 1 to 255 of Intel HEX or S-record format
+//!             [default: 16]
+//!
+//!         --ecc <scheme>
+//!             Check bits concatenated to words: even, odd, or hamming-<n>-<k>
+//!
+//!     -l, --lowercase
+//!             Lowercase hexadecimal digits except of Intel HEX and S-record
+//!
+//!         --lowercase-keywords
+//!             Lowercase keywords in MIF as in width instead of WIDTH
+//!
+//!     -c, --compact
+//!             No blank lines between header sections in MIF
+//!
+//!         --stamp                           Stamps version of mif on top of MIF
+//!         --header-first
+//!             Comments after WIDTH, DEPTH, and radix header of MIF
+//!
+//!         --no-runs
+//!             Writes every address on its own line instead of as ranges
+//!
+//!         --min-run <words>
+//!             Minimum words of runs written as ranges of MIF or VHDL format
+//!             [default: 2] or marked by address of readmemh format, shorter ones
+//!             on their own lines
+//!
+//!         --align-columns
+//!             Aligns colons and words of all lines in MIF
+//!
+//!         --dialect <dialect>
+//!             MIF dialect: quartus, generic, or gowin [default: quartus]
+//!
+//!         --address-digits <digits>
+//!             Fixed count of address digits in MIF [default: fewest required]
+//!
+//!         --comment-first
+//!             Comments LSB/MSB first as FIRST=lsb or FIRST=msb in MIF
+//!
+//!         --origin <address>
+//!             Signed origin added to addresses as written in MIF, say -0x10
+//!             [default: 0]
+//!
+//!         --addr-step <step>
+//!             Address stride per word in MIF, say 4 to address 32-bit words by
+//!             bytes [default: 1]
+//!
+//!         --and-mask <word>
+//!             Mask ANDed with words as written in MIF, say 0xff00
+//!
+//!         --or-mask <word>
+//!             Mask ORed with words as written in MIF after the AND mask
+//!
+//!         --offset <bytes>                  Byte offset into input [default: 0]
+//!         --length <bytes>
+//!             Count of input bytes from offset [default: to end of input]
+//!
+//!         --max-input-bytes <bytes>         Maximum count of input bytes
+//!         --pad-to <byte>
+//!             Pads input to next word with byte instead of erroring, say 0xff
+//!
+//!         --block-crc <words>
+//!             Comments CRC-32 of every block of words in MIF
+//!
+//!     -q, --quiet                           Prints no warnings, errors only
+//!     -v, --verbose
+//!             Prints progress and summary to standard error
+//!
+//!     -h, --help                            Prints help information
+//!     -V, --version                         Prints version information
+//! ```
+//!
+//! ## Info Subcommand
+//!
+//! ```text
+//! mif-info
+//! Prints binary's MIF information
+//!
+//! USAGE:
+//!     mif info [OPTIONS] [input]
 //!
 //! ARGS:
 //!     <input>    Input file or standard input (-) [default: -]
 //!
 //! OPTIONS:
-//!     -w, --width <bits>       Word width in bits from 1 to 128 [default: 16]
-//!     -f, --first <lsb|msb>    LSB/MSB first (little/big-endian) [default: lsb]
-//!     -h, --help               Prints help information
-//!     -V, --version            Prints version information
+//!     -w, --width <bits>
+//!             Word width in bits from 1 to 128 [default: 16]
+//!
+//!     -f, --first <lsb|msb|native>
+//!             LSB/MSB first (little/big-endian) or native [default: lsb]
+//!
+//!         --count <word>
+//!             Counts addresses holding word, say 0xff, as word-count and word-
+//!             ratio
+//!
+//!     -q, --quiet                     Prints no warnings, errors only
+//!     -v, --verbose
+//!             Prints progress and summary to standard error
+//!
+//!     -h, --help                      Prints help information
+//!     -V, --version                   Prints version information
 //! ```
 //!
 //! ## Join Subcommand
@@ -88,11 +254,64 @@
 //!     <toml>    TOML file or standard input (-) [default: -]
 //!
 //! OPTIONS:
-//!     -i, --bins <path>    Input directory [default: .]
-//!     -o, --mifs <path>    Output directory [default: .]
-//!     -n, --no-comments    No comments in MIFs
-//!     -h, --help           Prints help information
-//!     -V, --version        Prints version information
+//!     -i, --bins <path>                Input directory [default: .]
+//!     -t, --toml-dir <path>
+//!             Base directory of relative input and output directories and of piped
+//!             TOML's includes [default: .]
+//!
+//!     -o, --mifs <path>                Output directory [default: .]
+//!     -n, --no-comments                No comments in MIFs
+//!         --verbose-comments
+//!             Comments runs with their binaries and skipped areas in MIFs
+//!
+//!         --inline-comments
+//!             Comments areas inline where they begin instead of on top of MIFs
+//!
+//!     -s, --sort
+//!             Joins binaries sorted by path instead of in given order
+//!
+//!         --sort-areas
+//!             Joins areas of binaries sorted by offset instead of in given order
+//!
+//!     -l, --lowercase                  Lowercase hexadecimal digits in MIFs
+//!         --lowercase-keywords
+//!             Lowercase keywords in MIFs as in width instead of WIDTH
+//!
+//!     -c, --compact
+//!             No blank lines between header sections in MIFs
+//!
+//!         --stamp                      Stamps version of mif on top of MIFs
+//!         --header-first
+//!             Comments after WIDTH, DEPTH, and radix header of MIFs
+//!
+//!         --align-columns
+//!             Aligns colons and words of all lines in MIFs
+//!
+//!         --dialect <dialect>
+//!             MIF dialect: quartus, generic, or gowin [default: quartus]
+//!
+//!         --address-digits <digits>
+//!             Fixed count of address digits in MIFs [default: fewest required]
+//!
+//!         --addr-step <step>
+//!             Address stride per word in MIFs, say 4 to address 32-bit words by
+//!             bytes [default: 1]
+//!
+//!         --record-bytes <bytes>
+//!             Data bytes per record from 1 to 255 of Intel HEX or S-record MIFs
+//!             [default: 16]
+//!
+//!     -d, --max-depth <words>          Maximum depth of MIFs in words
+//!     -m, --manifest <path>            Manifest TOML file or standard output (-)
+//!     -j, --jobs <count>
+//!             Maximum count of threads [default: count of CPUs]
+//!
+//!     -q, --quiet                      Prints no warnings, errors only
+//!     -v, --verbose
+//!             Prints progress and summary to standard error
+//!
+//!     -h, --help                       Prints help information
+//!     -V, --version                    Prints version information
 //! ```
 //!
 //! ### Join Example
@@ -109,13 +328,41 @@
 //! is dumped to its own separate MIF for verification. Then, between program
 //! and data area is supposed to be an unused area of `0xffffff` words, which
 //! should be skipped. Listing them in the `skips` instruction will verify that
-//! this area only contains these words.
+//! this area only contains these words. Ranges of words as in
+//! `{ from = 0x00, to = 0x0f }` can be listed as well. An area of variable depth
+//! terminated by a sentinel word, say `0xffff`, can be read with `until = 0xffff`
+//! instead, in which case `depth` limits its depth including the sentinel word.
+//!
+//! An area holding a pattern once can be tiled with `repeat = 4`, in
+//! which case the pattern is joined four times.
+//! A top-level `[defaults]` table, say with `width = 24`, applies its `first`,
+//! `width`, and `input-format` to all areas omitting them. A top-level `[mifs]`
+//! table declares the expected depth of joined MIFs, erroring if any area has
+//! been forgotten, and their expected CRC-32 as listed in the manifest, erroring
+//! if any MIF has changed. A top-level `include = ["banks.toml"]` loads further
+//! TOML files relative to the including one beforehand, each with its own
+//! defaults, erroring on include cycles or binaries listed twice across files.
+//! Binaries given as Intel HEX or S-record are read with
+//! `input-format = "ihex"` or `input-format = "srec"` verifying their checksums.
+//! An area given with its byte offset as in `offset = 3801` is verified to
+//! neither overlap nor leave a gap to its preceding area. Areas given with their
+//! offsets are joined in the order of their offsets with `mif join --sort-areas`.
+//! Words bit-packed back-to-back as in two 4-bit words per byte are read with
+//! `packed = true` instead of aligned to bytes. Scrambled words are reassembled
+//! with `field-map` listing the source bit of every bit from the least-significant
+//! bit on, say `[{ from = 8, to = 15 }, { from = 0, to = 7 }]` swapping bytes.
+//! MIFs to join with extension `.hex`, `.srec`, `.coe`, `.vhd`, or `.h` are written
+//! as Intel HEX, S-record, COE, VHDL, or C instead, say
+//! `joins = ["a.mif", "a.hex"]` writes the same content in both formats at once.
+//! With extension `.bin`, raw bytes are written LSB/MSB first as the joined memory
+//! areas have been read.
 //!
 //! ```toml
 //! [["a.rom"]]
 //! first = "lsb" # Least-significant byte first. Default, can be omitted.
 //! width = 24
 //! depth = 1267
+//! name = "a.prog" # Comments area with name instead of path. Optional.
 //! joins = ["a.prog.mif", "ab.prog.mif"]
 //! [["a.rom"]]
 //! first = "lsb" # Least-significant byte first. Default, can be omitted.
@@ -139,30 +386,104 @@
 //! [["b.rom"]]
 //! depth = 1024
 //! joins = ["b.data.mif", "ab.data.mif"]
+//!
+//! [mifs."ab.prog.mif"]
+//! depth = 3014 # Ensures both program areas are joined. Optional.
+//! crc32 = 0x1c291ca3 # Ensures the same MIF as in `mif join -m`. Optional.
+//! ```
+//!
+//! Paths of binaries and MIFs may start with `~` denoting the home directory and
+//! may contain environment variables as in `$ROMDIR/a.rom` or `${ROMDIR}/a.rom`.
+//!
+//! ## Convert Subcommand
+//!
+//! ```text
+//! mif-convert
+//! Converts between formats
+//!
+//! USAGE:
+//!     mif convert [OPTIONS] [ARGS]
+//!
+//! ARGS:
+//!     <input>     Input file or standard input (-) [default: -]
+//!     <output>    Output file or standard output (-) [default: -]
+//!
+//! OPTIONS:
+//!         --from <format>
+//!             Input format: bin, mif, ihex, or srec [default: bin]
+//!
+//!         --to <format>
+//!             Output format: bin, mif, ihex, srec, coe, readmemh, vhdl, or c
+//!             [default: mif]
+//!
+//!     -w, --width <bits>
+//!             Word width in bits from 1 to 128 of non-MIF input
+//!
+//!     -f, --first <lsb|msb|native>
+//!             LSB/MSB first (little/big-endian) or native of byte-oriented formats
+//!             [default: as commented in MIF input or lsb]
+//!
+//!         --strict                    Rejects non-canonical MIF input
+//!     -l, --lowercase
+//!             Lowercase hexadecimal digits except of Intel HEX and S-record output
+//!
+//!     -c, --compact
+//!             No blank lines between header sections in MIF output
+//!
+//!         --stamp                     Stamps version of mif on top of MIF output
+//!         --record-bytes <bytes>
+//!             Data bytes per record from 1 to 255 of Intel HEX or S-record output
+//!             [default: 16]
+//!
+//!         --min-run <words>
+//!             Minimum words of runs written as ranges of MIF or VHDL output
+//!             [default: 2] or marked by address of readmemh output, shorter ones
+//!             on their own lines
+//!
+//!     -q, --quiet                     Prints no warnings, errors only
+//!     -v, --verbose
+//!             Prints progress and summary to standard error
+//!
+//!     -h, --help                      Prints help information
+//!     -V, --version                   Prints version information
 //! ```
+//!
+//! Converts between formats as in `mif convert --from mif --to ihex a.mif a.hex`.
+//! LSB/MSB first defaults to the one commented in MIF input, see `mif dump
+//! --comment-first`.
 
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "mmap"), forbid(unsafe_code))]
+#![cfg_attr(feature = "mmap", deny(unsafe_code))]
 #![forbid(missing_docs)]
 
 /// Command-line interface functionality of `mif` binary.
 #[cfg(feature = "cli")]
 pub mod cli;
 #[cfg(feature = "cli")]
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+mod format;
+pub mod parse;
 
 use std::{
-	mem::size_of,
-	path::PathBuf,
-	io::{self, Read, Write},
+	mem::{size_of, take},
+	any::type_name,
+	path::{PathBuf, Path},
+	io::{self, Read, Write, ErrorKind},
 	result,
-	fmt::UpperHex,
+	fmt::{self, UpperHex, Display, Formatter},
 	str::FromStr,
+	convert::TryFrom,
+	iter::once,
+	ops::Range,
+	num::NonZeroUsize,
+	collections::BTreeMap,
 };
 use num_traits::{
 	sign::Unsigned, int::PrimInt, cast::FromPrimitive,
 	ops::{checked::CheckedShl, wrapping::WrappingSub},
 };
-use byteorder::{LE, BE, ReadBytesExt};
+use byteorder::{LE, BE, ByteOrder, ReadBytesExt};
 use thiserror::Error;
 use First::{Lsb, Msb};
 use Error::*;
@@ -173,32 +494,130 @@ type Result<T> = result::Result<T, Error>;
 #[derive(Error, Debug)]
 #[non_exhaustive]
 pub enum Error {
-	/// Neither `"lsb"` nor `"msb"` first.
-	#[error("Valid values are `lsb` and `msb`")]
+	/// Neither `"lsb"`, `"msb"`, nor `"native"` first.
+	#[error("Valid values are `lsb`, `msb`, and `native`")]
 	NeitherLsbNorMsbFirst,
-	/// Width exceeds `[1, Mif::max_width()]`
-	#[error("Width {0} out of [1, {1}]")]
-	WidthOutOfRange(usize, usize),
+	/// Width exceeds `[1, Mif::max_width()]` of word type as in `u16`.
+	#[error("Width {0} out of [1, {1}] of `{2}` words")]
+	WidthOutOfRange(usize, usize, &'static str),
 	/// Word value exceeds `Mif::max_value()`.
 	#[error("Word at depth {0} out of width {1}")]
 	ValueOutOfWidth(usize, usize),
+	/// Word value exceeds word type as in `u64`, see `Mif::convert()`.
+	#[error("Word at depth {0} out of `{1}` words")]
+	ValueOutOfType(usize, &'static str),
+	/// Neither of the valid `Format` values.
+	#[error("Valid values are `mif`, `ihex`, `srec`, `coe`, `readmemh`, \
+		`vhdl`, and `c`")]
+	UnknownFormat,
+	/// Neither of the valid `Dialect` values.
+	#[error("Valid values are `quartus`, `generic`, and `gowin`")]
+	UnknownDialect,
+	/// Neither of the valid `Ecc` values.
+	#[error("Valid values are `even`, `odd`, and `hamming-n-k`")]
+	UnknownEcc,
+	/// Hamming code mismatching width or of too few check bits.
+	#[error("Invalid Hamming({0}, {1}) code")]
+	InvalidHamming(usize, usize),
+	/// Address exceeds address space of format.
+	#[error("Address {0} out of address space")]
+	AddressOutOfRange(usize),
+	/// Data bytes per record out of range of format.
+	#[error("Record bytes {0} out of 1 through {1}")]
+	RecordOutOfRange(usize, usize),
+	/// Width narrower than current width.
+	#[error("Width {0} narrower than {1}")]
+	NarrowerWidth(usize, usize),
+	/// Width wider than current width.
+	#[error("Width {0} wider than {1}")]
+	WiderWidth(usize, usize),
+	/// Depth exceeds maximum depth.
+	#[error("Depth {0} exceeds {1}")]
+	DepthExceeded(usize, usize),
 	/// Less words read than expected.
 	#[error("Missing {0} words")]
 	MissingWords(usize),
+	/// End of input reached before sentinel word.
+	#[error("Missing sentinel word")]
+	MissingSentinel,
+	/// Invalid MIF syntax at line.
+	#[error("Invalid syntax at line {0}")]
+	Syntax(usize),
+	/// Invalid checksum of Intel HEX or S-record at line.
+	#[error("Invalid checksum at line {0}")]
+	Checksum(usize),
+	/// Address lower than address of preceding run.
+	#[error("Address {0} out of order")]
+	OutOfOrder(usize),
+	/// Address already given by preceding run.
+	#[error("Duplicate address {0}")]
+	DuplicateAddress(usize),
+	/// Run of same word as preceding adjacent run.
+	#[error("Non-coalesced run at address {0}")]
+	NonCoalesced(usize),
+	/// Run of zero bulk.
+	#[error("Empty run at address {0}")]
+	EmptyRun(usize),
+	/// Depth differing from sum of bulks.
+	#[error("Depth {0} instead of {1}")]
+	DepthMismatch(usize, usize),
+	/// Byte count no multiple of word alignment in bytes.
+	#[error("Partial word of {0} bytes out of {1}")]
+	PartialWord(usize, usize),
+	/// Address digits fewer than required by depth.
+	#[error("Address digits {0} fewer than {1}")]
+	FewerDigits(usize, usize),
+	/// Field map not covering width or of source bits out of word alignment.
+	#[error("Invalid field map of {0} bits for width {1}")]
+	InvalidFieldMap(usize, usize),
+	/// Group of bytes no divisor of word alignment, see `Mif::read_grouped()`.
+	#[error("Group of {0} bytes no divisor of {1} bytes")]
+	InvalidGroup(usize, usize),
+	/// Footer of too few or too many words for its checksum scheme.
+	#[error("Invalid footer of {0} words of width {1}")]
+	InvalidFooter(usize, usize),
+	/// Terminator word within string of string table.
+	#[error("Terminator word at depth {0} within string")]
+	EmbeddedTerminator(usize),
+	/// Empty MIF unrepresentable in format, e.g., as VHDL array.
+	#[error("Empty MIF unrepresentable as {0}")]
+	EmptyDepth(&'static str),
+	/// Width wider than widest default C type, see `Mif::write_c_header()`.
+	#[error("Width {0} wider than `uint64_t` of C")]
+	WidthOutOfCType(usize),
+	/// Name no C identifier, see `Mif::write_c_header()`.
+	#[error("Invalid C identifier `{0}`")]
+	InvalidIdentifier(String),
+	/// Style option unsupported by MIF dialect, see `Dialect::Gowin`.
+	#[error("Style unsupported by {0} dialect")]
+	UnsupportedStyle(&'static str),
 	/// I/O error.
 	#[error(transparent)]
 	IoError(#[from] io::Error),
 }
 
 /// Native MIF representation.
+///
+/// Equality compares memory areas and notes as well, see `eq_content()` to
+/// compare content only.
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Mif<T: UpperHex + Unsigned + PrimInt + FromPrimitive> {
 	width: usize,
 	depth: usize,
 	words: Vec<(T, usize)>,
 	areas: Vec<(usize, PathBuf)>,
+	notes: Vec<(usize, String)>,
 }
 
+/// Width, depth, words, memory areas, and notes of `Mif`.
+pub type Parts<T> = (
+	usize,
+	usize,
+	Vec<(T, usize)>,
+	Vec<(usize, PathBuf)>,
+	Vec<(usize, String)>,
+);
+
 impl<T> Mif<T>
 where
 	T: UpperHex + Unsigned + PrimInt + FromPrimitive + CheckedShl + WrappingSub,
@@ -206,10 +625,64 @@ where
 	/// Creates new MIF with word `width`.
 	pub fn new(width: usize) -> Result<Mif<T>> {
 		if (1..=Self::max_width()).contains(&width) {
-			Ok(Mif { words: Vec::new(), depth: 0, areas: Vec::new(), width })
+			Ok(Mif {
+				words: Vec::new(),
+				depth: 0,
+				areas: Vec::new(),
+				notes: Vec::new(),
+				width,
+			})
 		} else {
-			Err(WidthOutOfRange(width, Self::max_width()))
+			Err(Self::out_of_range(width))
+		}
+	}
+	/// Width out of range of `T` error.
+	fn out_of_range(width: usize) -> Error {
+		WidthOutOfRange(width, Self::max_width(), type_name::<T>())
+	}
+	/// Creates new MIF from its `Parts`, see `into_parts()`.
+	///
+	/// Moves the vectors in without copying and errors with the first violated
+	/// invariant, see `validate()`.
+	pub fn from_parts(
+		(width, depth, words, areas, notes): Parts<T>,
+	) -> Result<Self> {
+		let mif = Mif { width, depth, words, areas, notes };
+		mif.validate()?;
+		Ok(mif)
+	}
+	/// Moves out `Parts` without copying, see `from_parts()`.
+	pub fn into_parts(self) -> Parts<T> {
+		(self.width, self.depth, self.words, self.areas, self.notes)
+	}
+	/// Converts into MIF of word type `U` as in `Mif<u64>` from `Mif<u128>`,
+	/// see `TryFrom`.
+	///
+	/// Errors with the address of the first word out of `U` or if the width
+	/// exceeds `Mif::<U>::max_width()`.
+	pub fn convert<U>(self) -> Result<Mif<U>>
+	where
+		U: UpperHex + Unsigned + PrimInt + FromPrimitive + CheckedShl
+			+ WrappingSub,
+	{
+		let mut addr = 0;
+		let mut words = Vec::with_capacity(self.words.len());
+		for (word, bulk) in self.words {
+			let word = word.to_u128().and_then(U::from_u128)
+				.ok_or(ValueOutOfType(addr, type_name::<U>()))?;
+			words.push((word, bulk));
+			addr += bulk;
+		}
+		if self.width > Mif::<U>::max_width() {
+			return Err(Mif::<U>::out_of_range(self.width));
 		}
+		Ok(Mif {
+			width: self.width,
+			depth: self.depth,
+			words,
+			areas: self.areas,
+			notes: self.notes,
+		})
 	}
 	/// Maximum word width in bits depending on `T`.
 	pub fn max_width() -> usize {
@@ -221,33 +694,394 @@ where
 	}
 	/// Maximum word value depending on `width()`.
 	pub fn max_value(&self) -> T {
-		T::one().checked_shl(self.width as u32)
+		Self::max_value_of(self.width)
+	}
+	/// Maximum word value depending on `width`.
+	fn max_value_of(width: usize) -> T {
+		T::one().checked_shl(width as u32)
 			.unwrap_or(T::zero()).wrapping_sub(&T::one())
 	}
 	/// Word width in bits.
 	pub fn width(&self) -> usize {
 		self.width
 	}
+	/// Re-declares word `width` as when correcting a misdeclared width after
+	/// reading, keeping the runs as they are.
+	///
+	/// Widens or narrows depending on `width()`, see `widen()` and `narrow()`.
+	/// Narrowing errors with the address of the first word out of the new
+	/// width, leaving the MIF unchanged on error. The word width in bytes
+	/// follows, see `align()`.
+	pub fn set_width(&mut self, width: usize) -> Result<()> {
+		if width < self.width {
+			self.narrow(width)
+		} else {
+			self.widen(width)
+		}
+	}
 	/// Word width in bytes.
 	pub fn align(&self) -> usize {
 		(self.width as f64 / 8.0).ceil() as usize
 	}
+	/// Word width in hexadecimal digits.
+	fn word_pads(&self) -> usize {
+		(self.width as f64 / 4.0).ceil() as usize
+	}
+	/// Hexadecimal digits of the first or last address displayed relative to
+	/// `origin` and stepped by `step` per word, whichever is greater in
+	/// magnitude, zero if both are zero.
+	///
+	/// Computed exactly as floating-point logarithms fall short for depths just
+	/// above large powers of 16. Errors if stepping addresses up to `depth()`,
+	/// where memory areas and notes may end, exceeds the address space.
+	fn addr_pads(&self, origin: isize, step: usize) -> Result<usize> {
+		if self.depth.checked_mul(step).is_none() {
+			Err(AddressOutOfRange(self.depth))?;
+		}
+		let first = (origin as i128).unsigned_abs();
+		let last = (origin as i128 + (self.max_address() * step) as i128)
+			.unsigned_abs();
+		Ok((u128::BITS - first.max(last).leading_zeros()).div_ceil(4) as usize)
+	}
 	/// MIF depth in words.
 	pub fn depth(&self) -> usize {
 		self.depth
 	}
+	/// Last address, that is `depth() - 1`, or zero if empty.
+	///
+	/// Determines the count of address digits, e.g. one digit for depth 16.
+	pub fn max_address(&self) -> usize {
+		self.depth.saturating_sub(1)
+	}
+	/// MIF depth in bytes, that is `depth() * align()`.
+	pub fn depth_bytes(&self) -> usize {
+		self.depth * self.align()
+	}
+	/// Address width in bits, that is `ceil(log2(depth()))`.
+	///
+	/// Zero bits if empty and one bit if of depth one.
+	pub fn address_width_bits(&self) -> usize {
+		match self.depth {
+			0 => 0,
+			1 => 1,
+			_ => (usize::BITS - self.max_address().leading_zeros()) as usize,
+		}
+	}
+	/// Whether MIF fits into memory of `capacity` bytes, see `depth_bytes()`.
+	pub fn fits_in(&self, capacity: usize) -> bool {
+		self.depth_bytes() <= capacity
+	}
 	/// Reference to words and their bulk in given order.
 	pub fn words(&self) -> &Vec<(T, usize)> {
 		&self.words
 	}
+	/// Count of runs of words, that is `words().len()`.
+	pub fn run_count(&self) -> usize {
+		self.words.len()
+	}
+	/// Ratio of runs to words, that is `run_count() / depth()`, zero if empty.
+	///
+	/// One if no adjacent words are equal and `1 / depth()` if all words are.
+	pub fn compression_ratio(&self) -> f64 {
+		if self.depth == 0 {
+			0.0
+		} else {
+			self.run_count() as f64 / self.depth as f64
+		}
+	}
 	/// Reference to addresses and paths of memory areas in given order.
 	pub fn areas(&self) -> &Vec<(usize, PathBuf)> {
 		&self.areas
 	}
+	/// Verifies invariants and returns the first violated one.
+	///
+	///   * `width()` is in the range of `1..=max_width()`, checked by `new()`.
+	///   * Every word fits into `width()`, checked by `push()`.
+	///   * Every run has a non-zero bulk, ensured by `push()`.
+	///   * Adjacent runs differ in their words, ensured by `push()`.
+	///   * `depth()` equals the sum of all bulks, ensured by `push()`.
+	///   * Addresses of memory areas and notes are in order and within
+	///     `depth()`, ensured by `area()` and `note()`.
+	///
+	/// Any MIF built via public methods upholds these invariants, making this
+	/// a sanity check in `O(runs)` for MIFs of unknown origin.
+	pub fn validate(&self) -> Result<()> {
+		if !(1..=Self::max_width()).contains(&self.width) {
+			Err(Self::out_of_range(self.width))?;
+		}
+		let mut addr = 0;
+		let mut last = None;
+		for &(word, bulk) in &self.words {
+			if word > self.max_value() {
+				Err(ValueOutOfWidth(addr, self.width))?;
+			}
+			if bulk == 0 {
+				Err(EmptyRun(addr))?;
+			}
+			if last == Some(word) {
+				Err(NonCoalesced(addr))?;
+			}
+			last = Some(word);
+			addr += bulk;
+		}
+		if addr != self.depth {
+			Err(DepthMismatch(self.depth, addr))?;
+		}
+		let areas = self.areas.iter().map(|&(addr, _)| addr);
+		let notes = self.notes.iter().map(|&(addr, _)| addr);
+		for addrs in [areas.collect::<Vec<_>>(), notes.collect()] {
+			let mut last = 0;
+			for addr in addrs {
+				if addr < last {
+					Err(OutOfOrder(addr))?;
+				}
+				if addr > self.depth {
+					Err(AddressOutOfRange(addr))?;
+				}
+				last = addr;
+			}
+		}
+		Ok(())
+	}
+	/// Whether content equals `other`'s ignoring memory areas and notes.
+	///
+	/// Compares width, depth, and words only whereas `==` compares memory areas
+	/// and notes as well.
+	pub fn eq_content(&self, other: &Self) -> bool {
+		self.width == other.width && self.depth == other.depth
+			&& self.words == other.words
+	}
+	/// Regions of addresses at which words differ from `other`, compared run
+	/// by run instead of address by address.
+	///
+	/// Runs of both MIFs are split where the other's runs begin, so a changed
+	/// word within a long run is reported as a region of one address and a
+	/// replaced run as one region. Words beyond the shallower depth are `None`.
+	pub fn diff_runs(&self, other: &Self) -> Vec<RunDiff<T>> {
+		let mut diffs = Vec::new();
+		let mut a_runs = self.words.iter().copied();
+		let mut b_runs = other.words.iter().copied();
+		let (mut a_run, mut b_run) = (a_runs.next(), b_runs.next());
+		let mut start = 0;
+		loop {
+			let bulk = match (a_run, b_run) {
+				(Some((_, a_bulk)), Some((_, b_bulk))) => a_bulk.min(b_bulk),
+				(Some((_, bulk)), None) | (None, Some((_, bulk))) => bulk,
+				(None, None) => break,
+			};
+			let a_word = a_run.map(|(word, _bulk)| word);
+			let b_word = b_run.map(|(word, _bulk)| word);
+			if a_word != b_word {
+				let end = start + bulk;
+				diffs.push(RunDiff { start, end, a_word, b_word });
+			}
+			start += bulk;
+			let runs = [(&mut a_run, &mut a_runs), (&mut b_run, &mut b_runs)];
+			for (run, runs) in runs {
+				*run = match *run {
+					Some((word, rest)) if rest > bulk =>
+						Some((word, rest - bulk)),
+					Some(_) => runs.next(),
+					None => None,
+				};
+			}
+		}
+		diffs
+	}
+	/// Word if all words are the same, `None` if empty or not uniform.
+	pub fn is_uniform(&self) -> Option<T> {
+		match self.words.as_slice() {
+			&[(word, _bulk)] => Some(word),
+			_ => None,
+		}
+	}
+	/// Count of addresses holding `word`.
+	pub fn count_word(&self, word: T) -> usize {
+		self.words.iter().filter(|&&(run, _bulk)| run == word)
+			.map(|&(_word, bulk)| bulk).sum()
+	}
+	/// Most common word as fill candidate, the lowest one on ties.
+	pub fn fill_word(&self) -> Option<T> {
+		let mut bulks = BTreeMap::new();
+		for &(word, bulk) in &self.words {
+			*bulks.entry(word).or_insert(0) += bulk;
+		}
+		bulks.into_iter()
+			.max_by(|(a_word, a_bulk), (b_word, b_bulk)|
+				a_bulk.cmp(b_bulk).then(b_word.cmp(a_word)))
+			.map(|(word, _bulk)| word)
+	}
+	/// Fraction of addresses holding `word`, zero if empty.
+	pub fn fill_ratio_of(&self, word: T) -> f64 {
+		if self.depth == 0 {
+			0.0
+		} else {
+			self.count_word(word) as f64 / self.depth as f64
+		}
+	}
+	/// Most common word and its fraction of addresses, see `fill_word()`.
+	pub fn dominant_word(&self) -> Option<(T, f64)> {
+		self.fill_word().map(|word| (word, self.fill_ratio_of(word)))
+	}
+	/// Word at address `addr` or `None` if beyond `depth()`.
+	pub fn word_at(&self, addr: usize) -> Option<T> {
+		let mut first = 0;
+		self.words.iter().find(|&&(_word, bulk)| {
+			first += bulk;
+			addr < first
+		}).map(|&(word, _bulk)| word)
+	}
+	/// Runs within address `range` as in `(addr, bulk, word)` with runs at its
+	/// boundaries clipped to it.
+	///
+	/// Queries a window of words without expanding the runs.
+	pub fn runs_in_range(&self, range: Range<usize>)
+	-> impl Iterator<Item = (usize, usize, T)> + '_ {
+		let Range { start, end } = range;
+		let mut first = 0;
+		let runs = self.words.iter().map(move |&(word, bulk)| {
+			let addr = first;
+			first += bulk;
+			(addr, bulk, word)
+		});
+		runs.skip_while(move |&(addr, bulk, _word)| addr + bulk <= start)
+			.take_while(move |&(addr, _bulk, _word)| addr < end)
+			.map(move |(addr, bulk, word)| {
+				let from = addr.max(start);
+				(from, (addr + bulk).min(end).saturating_sub(from), word)
+			})
+			.filter(|&(_addr, bulk, _word)| bulk > 0)
+	}
+	/// Byte `index` of word at address `addr` as if written LSB/MSB-`first`,
+	/// see `write_bytes()`, or `None` if beyond `depth()` or `align()`.
+	///
+	/// The most-significant byte of a word whose width is no multiple of 8
+	/// bits is partial with its high bits zero.
+	pub fn byte_at(&self, addr: usize, index: usize, first: First)
+	-> Option<u8> {
+		let align = self.align();
+		if index >= align {
+			return None;
+		}
+		let word = self.word_at(addr)?.to_u128().unwrap();
+		let shift = match first.resolve() {
+			Msb => align - 1 - index,
+			_ => index,
+		} * 8;
+		Some((word >> shift) as u8)
+	}
+	/// CRC-32 of every `block` words as in `(addr, crc)`, see `crc32()`.
+	///
+	/// Words are checksummed as `align()` bytes LSB first each. The last block
+	/// is partial unless `depth()` is a multiple of `block`.
+	pub fn block_checksums(&self, block: NonZeroUsize) -> Vec<(usize, u32)> {
+		let block = block.get();
+		let align = self.align();
+		let mut sums = Vec::new();
+		for (addr, word) in self.expand().enumerate() {
+			if addr % block == 0 {
+				sums.push((addr, 0));
+			}
+			let (_addr, crc) = sums.last_mut().unwrap();
+			*crc = crc32(*crc, &word.to_u128().unwrap().to_le_bytes()[..align]);
+		}
+		sums
+	}
+	/// Trailing checksum of `words` words computed over the preceding words
+	/// according to `scheme`, see `Footer`.
+	///
+	/// Errors if there are fewer than `words` words or if the `words` words do
+	/// not fit the checksum into 128 bits or the CRC-16 into their bits.
+	pub fn trailing_checksum(&self, scheme: Footer, words: usize)
+	-> Result<u128> {
+		let bits = self.width.checked_mul(words)
+			.filter(|&bits| (1..=128).contains(&bits))
+			.filter(|&bits| scheme != Footer::Crc16 || bits >= 16)
+			.ok_or(InvalidFooter(words, self.width))?;
+		if words > self.depth {
+			Err(DepthExceeded(words, self.depth))?;
+		}
+		let mask = u128::MAX >> (128 - bits);
+		let body = self.expand().take(self.depth - words)
+			.map(|word| word.to_u128().unwrap());
+		Ok(match scheme {
+			Footer::SumToZero => body.fold(0u128, u128::wrapping_add)
+				.wrapping_neg() & mask,
+			Footer::SumComplement => !body.fold(0u128, u128::wrapping_add)
+				& mask,
+			Footer::Crc16 => {
+				let align = self.align();
+				body.fold(0xffff, |crc, word|
+					crc16(crc, &word.to_le_bytes()[..align])) as u128
+			},
+		})
+	}
+	/// Patches trailing checksum of `words` words computed over the preceding
+	/// words according to `scheme` into these words, see `trailing_checksum()`.
+	///
+	/// Multi-word checksums are split into words from the least-significant
+	/// bits on. Returns the checksum.
+	pub fn apply_trailing_checksum(&mut self, scheme: Footer, words: usize)
+	-> Result<u128> {
+		let sum = self.trailing_checksum(scheme, words)?;
+		let mask = self.max_value().to_u128().unwrap();
+		let mut patch = Self::new(self.width)?;
+		for word in 0..words {
+			patch.push_word(T::from_u128(sum >> (word * self.width) & mask)
+				.unwrap())?;
+		}
+		self.overlay(&patch, self.depth - words)?;
+		Ok(sum)
+	}
+	/// Whether the trailing `words` words hold the checksum computed over the
+	/// preceding words according to `scheme`, see `trailing_checksum()`.
+	pub fn has_trailing_checksum(&self, scheme: Footer, words: usize)
+	-> Result<bool> {
+		let sum = self.trailing_checksum(scheme, words)?;
+		let footer = self.expand().skip(self.depth - words).enumerate()
+			.fold(0u128, |footer, (word, value)|
+				footer | value.to_u128().unwrap() << (word * self.width));
+		Ok(footer == sum)
+	}
+	/// Words one by one with their bulk expanded.
+	fn expand(&self) -> impl Iterator<Item = T> + '_ {
+		self.words.iter().flat_map(|&(word, bulk)| (0..bulk).map(move |_| word))
+	}
+	/// Runs of words with runs of fewer than `min_run` words repeated as single
+	/// words of bulk one, two by default, see `Style::min_run`.
+	///
+	/// Shared by the formats writing runs as ranges of addresses.
+	fn runs(&self, min_run: Option<usize>)
+	-> impl Iterator<Item = (T, usize)> + '_ {
+		let min_run = min_run.unwrap_or(2);
+		self.words.iter().flat_map(move |&(word, bulk)| {
+			let (count, bulk) =
+				if bulk < min_run { (bulk, 1) } else { (1, bulk) };
+			(0..count).map(move |_| (word, bulk))
+		})
+	}
+	/// Reference to addresses and notes in given order.
+	pub fn notes(&self) -> &Vec<(usize, String)> {
+		&self.notes
+	}
+	/// Paths of memory areas overlapping `bulk` words at address `addr`.
+	pub fn sources(&self, addr: usize, bulk: usize)
+	-> impl Iterator<Item = &PathBuf> {
+		let ends = self.areas.iter().skip(1).map(|&(addr, _)| addr)
+			.chain(once(self.depth));
+		self.areas.iter().zip(ends)
+			.filter(move |&(&(first, _), last)|
+				first < last && first < addr + bulk && addr < last)
+			.map(|((_, path), _)| path)
+	}
 	/// Addresses memory `area` at current `depth()`.
 	pub fn area(&mut self, area: PathBuf) {
 		self.areas.push((self.depth, area));
 	}
+	/// Addresses `note` at current `depth()`, e.g. about skipped memory areas.
+	pub fn note(&mut self, note: String) {
+		self.notes.push((self.depth, note));
+	}
 	/// Pushes `word` or add up its `bulk`.
 	pub fn push(&mut self, word: T, bulk: usize) -> Result<()> {
 		match self.words.last_mut() {
@@ -265,80 +1099,1007 @@ where
 		self.depth += bulk;
 		Ok(())
 	}
+	/// Pushes single `word`, see `push()`.
+	pub fn push_word(&mut self, word: T) -> Result<()> {
+		self.push(word, 1)
+	}
+	/// Pushes single `words` in given order coalescing repeated words to runs.
+	pub fn push_slice(&mut self, words: &[T]) -> Result<()> {
+		words.iter().try_for_each(|&word| self.push_word(word))
+	}
+	/// Pushes `word` or add up its `bulk` unless exceeding `max_depth`.
+	///
+	/// Returns the remaining capacity in words.
+	pub fn push_bounded(&mut self, word: T, bulk: usize, max_depth: usize)
+	-> Result<usize> {
+		let depth = self.depth + bulk;
+		if depth > max_depth {
+			Err(DepthExceeded(depth, max_depth))?;
+		}
+		self.push(word, bulk)?;
+		Ok(max_depth - depth)
+	}
 	/// Joins in `other` MIF.
 	pub fn join(&mut self, other: &Self) -> Result<()> {
 		other.words.iter().try_for_each(|&(word, bulk)| self.push(word, bulk))
 	}
-	/// Reads `depth` LSB/MSB-`first` words from `bytes` reader.
-	pub fn read(&mut self, bytes: &mut dyn Read, depth: usize, first: First)
-	-> Result<()> {
-		let align = self.align();
-		let mut words = 0;
-		for _ in 0..depth {
-			let word = match first {
-				Lsb => bytes.read_uint128::<LE>(align),
-				Msb => bytes.read_uint128::<BE>(align),
-			}?;
-			self.push(T::from_u128(word)
-				.ok_or(ValueOutOfWidth(words, self.width))?, 1)?;
-			words += 1;
+	/// Joins in `other` MIF unless exceeding `max_depth`.
+	///
+	/// Returns the remaining capacity in words.
+	pub fn join_bounded(&mut self, other: &Self, max_depth: usize)
+	-> Result<usize> {
+		let depth = self.depth + other.depth;
+		if depth > max_depth {
+			Err(DepthExceeded(depth, max_depth))?;
 		}
-		if depth != words {
-			Err(MissingWords(depth - words))?;
+		self.join(other)?;
+		Ok(max_depth - depth)
+	}
+	/// Retains runs of `(word, bulk)` satisfying predicate `f`.
+	///
+	/// Remaining runs are coalesced and `depth()` is reduced by the bulk of the
+	/// dropped runs. Hence, addresses of subsequent runs, memory areas, and
+	/// notes shift down accordingly.
+	pub fn retain_runs<F: FnMut(T, usize) -> bool>(&mut self, mut f: F) {
+		let mut drops = Vec::new();
+		let mut addr = 0;
+		self.words.retain(|&(word, bulk)| {
+			let retain = f(word, bulk);
+			if !retain {
+				drops.push((addr, bulk));
+			}
+			addr += bulk;
+			retain
+		});
+		self.depth -= drops.iter().map(|&(_addr, bulk)| bulk).sum::<usize>();
+		self.dedup_runs();
+		let shift = |addr: &mut usize| {
+			let drop = drops.iter()
+				.map(|&(first, bulk)| bulk.min(addr.saturating_sub(first)))
+				.sum::<usize>();
+			*addr -= drop;
+		};
+		self.areas.iter_mut().for_each(|(addr, _)| shift(addr));
+		self.notes.iter_mut().for_each(|(addr, _)| shift(addr));
+	}
+	/// Merges adjacent runs of equal words and removes runs of zero bulk.
+	///
+	/// Restores the canonical form, see `validate()`, without changing any
+	/// addresses. Hence, it is idempotent.
+	pub fn dedup_runs(&mut self) {
+		self.words.retain(|&(_word, bulk)| bulk > 0);
+		self.words.dedup_by(|(word, bulk), (last_word, last_bulk)| {
+			let dedup = word == last_word;
+			if dedup {
+				*last_bulk += *bulk;
+			}
+			dedup
+		});
+	}
+	/// Resizes to `depth` words like `Vec::resize()`.
+	///
+	/// Grows by pushing `fill` words or shrinks by dropping trailing words,
+	/// splitting the run the new `depth()` ends within. Memory areas and notes
+	/// beyond the new `depth()` are dropped as well. Errors if `fill` exceeds
+	/// `width()`, even if it is not needed.
+	pub fn resize(&mut self, depth: usize, fill: T) -> Result<()> {
+		if depth >= self.depth {
+			return self.push(fill, depth - self.depth);
+		}
+		if fill > self.max_value() {
+			Err(ValueOutOfWidth(depth, self.width()))?;
+		}
+		let mut addr = 0;
+		let mut runs = 0;
+		while addr < depth {
+			addr += self.words[runs].1;
+			runs += 1;
 		}
+		self.words.truncate(runs);
+		if let Some((_word, bulk)) = self.words.last_mut() {
+			*bulk -= addr - depth;
+		}
+		self.depth = depth;
+		self.areas.retain(|&(addr, _)| addr <= depth);
+		self.notes.retain(|&(addr, _)| addr <= depth);
 		Ok(())
 	}
-	/// Writes MIF to writer.
+	/// Overwrites words at address `at` with words of `patch`.
 	///
-	///   * `lines`: Writer, MIF is written to.
-	///   * `areas`: Whether to comment memory areas as in `-- 0000: name.bin`.
-	pub fn write(&self, lines: &mut dyn Write, areas: bool) -> Result<()> {
-		let addr_pads = (self.depth as f64).log(16.0).ceil() as usize;
-		let word_pads = (self.width as f64 / 4.0).ceil() as usize;
-		if areas && !self.areas.is_empty() {
-			for (addr, path) in &self.areas {
-				writeln!(lines, "-- {:02$X}: {}",
-					addr, path.display(), addr_pads)?;
+	/// Runs are split at the boundaries of `patch` and coalesced again. Memory
+	/// areas and notes of `patch` are ignored. Errors if `patch` is wider than
+	/// `width()` or extends beyond `depth()`, see `overlay_grow()` otherwise.
+	pub fn overlay(&mut self, patch: &Self, at: usize) -> Result<()> {
+		self.overlay_with(patch, at, false)
+	}
+	/// Overwrites words at address `at` with words of `patch`, growing
+	/// `depth()` as necessary with zero words filling any gap, see
+	/// `overlay()`.
+	pub fn overlay_grow(&mut self, patch: &Self, at: usize) -> Result<()> {
+		self.overlay_with(patch, at, true)
+	}
+	/// Places words of `other` at address `at` at or beyond `depth()`, filling
+	/// any gap with `fill` words.
+	///
+	/// Supports sparse assembly of pieces living at fixed addresses. Memory
+	/// areas and notes of `other` are ignored. Errors if `other` is wider than
+	/// `width()` or if `at` is below `depth()`, see `overlay()` otherwise.
+	pub fn place(&mut self, other: &Self, at: usize, fill: T) -> Result<()> {
+		if other.width > self.width {
+			Err(WiderWidth(other.width, self.width))?;
+		}
+		if at < self.depth {
+			Err(OutOfOrder(at))?;
+		}
+		self.resize(at, fill)?;
+		self.join(other)
+	}
+	/// Overwrites words at address `at` with words of `patch` and `grow`s.
+	fn overlay_with(&mut self, patch: &Self, at: usize, grow: bool)
+	-> Result<()> {
+		if patch.width > self.width {
+			Err(WiderWidth(patch.width, self.width))?;
+		}
+		let end = at.checked_add(patch.depth)
+			.ok_or(DepthExceeded(usize::MAX, self.depth))?;
+		if end > self.depth && !grow {
+			Err(DepthExceeded(end, self.depth))?;
+		}
+		let words = take(&mut self.words);
+		self.depth = 0;
+		let mut addr = 0;
+		for &(word, bulk) in &words {
+			if addr < at {
+				self.push(word, (addr + bulk).min(at) - addr)?;
 			}
-			writeln!(lines)?;
+			addr += bulk;
 		}
-		writeln!(lines, "\
-			WIDTH={};\n\
-			DEPTH={};\n\
-			\n\
-			ADDRESS_RADIX=HEX;\n\
-			DATA_RADIX=HEX;\n\
-			\n\
-			CONTENT BEGIN", self.width, self.depth)?;
+		self.push(T::zero(), at - self.depth)?;
+		for &(word, bulk) in &patch.words {
+			self.push(word, bulk)?;
+		}
+		let mut addr = 0;
+		for &(word, bulk) in &words {
+			if addr + bulk > end {
+				self.push(word, addr + bulk - end.max(addr))?;
+			}
+			addr += bulk;
+		}
+		Ok(())
+	}
+	/// Widens word width to `width` by zero-extending words.
+	///
+	/// Errors if `width` is narrower than `width()` or wider than
+	/// `max_width()`.
+	pub fn widen(&mut self, width: usize) -> Result<()> {
+		if width > Self::max_width() {
+			Err(Self::out_of_range(width))?;
+		}
+		if width < self.width {
+			Err(NarrowerWidth(width, self.width))?;
+		}
+		self.width = width;
+		Ok(())
+	}
+	/// Narrows word width to `width` if all words fit.
+	///
+	/// Errors if `width` is zero or wider than `width()` or with the address
+	/// of the first word exceeding the maximum word value of `width`.
+	pub fn narrow(&mut self, width: usize) -> Result<()> {
+		if width < 1 {
+			Err(Self::out_of_range(width))?;
+		}
+		if width > self.width {
+			Err(WiderWidth(width, self.width))?;
+		}
+		let max_value = Self::max_value_of(width);
 		let mut addr = 0;
 		for &(word, bulk) in &self.words {
-			if bulk == 1 {
-				writeln!(lines, "\t{:02$X}  :   {:03$X};",
-					addr, word, addr_pads, word_pads)?;
-			} else {
-				writeln!(lines, "\t[{:03$X}..{:03$X}]  :   {:04$X};",
-					addr, addr + bulk - 1, word, addr_pads, word_pads)?;
+			if word > max_value {
+				Err(ValueOutOfWidth(addr, width))?;
 			}
 			addr += bulk;
 		}
-		writeln!(lines, "END;")?;
+		self.width = width;
 		Ok(())
 	}
-}
-
-/// LSB/MSB first (little/big-endian).
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
-#[cfg_attr(feature = "cli", derive(Deserialize))]
-#[cfg_attr(feature = "cli", serde(rename_all = "kebab-case"))]
-pub enum First {
-	/// Least-significant byte first (little-endian).
-	Lsb,
+	/// MIF of words extended by their check bits of `ecc` scheme.
+	///
+	/// Check bits are concatenated above the data bits, that is the words are
+	/// widened by `Ecc::checks()` bits. Memory areas and notes are retained.
+	pub fn with_parity(&self, ecc: Ecc) -> Result<Self> {
+		self.map_checks(ecc, true)
+	}
+	/// Companion MIF of check bits of `ecc` scheme per word, see
+	/// `with_parity()`.
+	pub fn parity(&self, ecc: Ecc) -> Result<Self> {
+		self.map_checks(ecc, false)
+	}
+	/// MIF of check bits of `ecc` scheme with or without `data` bits.
+	fn map_checks(&self, ecc: Ecc, data: bool) -> Result<Self> {
+		let checks = ecc.checks(self.width)?;
+		let shift = if data { self.width } else { 0 };
+		let mut mif = Self::new(shift + checks)?;
+		mif.areas = self.areas.clone();
+		mif.notes = self.notes.clone();
+		for &(word, bulk) in &self.words {
+			let word = word.to_u128().unwrap();
+			let check = ecc.check(word) << shift;
+			let word = if data { word | check } else { check };
+			mif.push(T::from_u128(word).unwrap(), bulk)?;
+		}
+		Ok(mif)
+	}
+	/// Appends LSB/MSB-`first` words from `bytes` as they arrive.
+	///
+	/// Unlike `read()`, any count of bytes is accepted as long as it is a
+	/// multiple of `align()`. Runs are merged across subsequent calls. On any
+	/// error, nothing is appended.
+	pub fn append_bytes(&mut self, bytes: &[u8], first: First) -> Result<()> {
+		let first = first.resolve();
+		let align = self.align();
+		let partial = bytes.len() % align;
+		if partial != 0 {
+			Err(PartialWord(partial, align))?;
+		}
+		// Addresses errors as if words were pushed to `self` directly.
+		let mut mif = Self::new(self.width)?;
+		mif.depth = self.depth;
+		for word in bytes.chunks(align) {
+			let word = match first {
+				Msb => BE::read_uint128(word, align),
+				_ => LE::read_uint128(word, align),
+			};
+			mif.push_word(T::from_u128(word)
+				.ok_or(ValueOutOfWidth(mif.depth, mif.width))?)?;
+		}
+		self.join(&mif)
+	}
+	/// Creates new MIF with word `width` from string table of byte `strings`.
+	///
+	/// Every byte is stored as its own word and every string is delimited
+	/// according to `delimiter`, see `strings()` for the inverse.
+	pub fn from_strings<S: AsRef<[u8]>>(
+		width: usize,
+		strings: &[S],
+		delimiter: Delimiter<T>,
+	) -> Result<Self> {
+		let mut mif = Self::new(width)?;
+		for string in strings {
+			let string = string.as_ref();
+			if let Delimiter::Prefix = delimiter {
+				mif.push(T::from_usize(string.len())
+					.ok_or(ValueOutOfWidth(mif.depth, width))?, 1)?;
+			}
+			for &byte in string {
+				let word = T::from_u8(byte)
+					.ok_or(ValueOutOfWidth(mif.depth, width))?;
+				if delimiter == Delimiter::Terminator(word) {
+					Err(EmbeddedTerminator(mif.depth))?;
+				}
+				mif.push_word(word)?;
+			}
+			if let Delimiter::Terminator(terminator) = delimiter {
+				mif.push_word(terminator)?;
+			}
+		}
+		Ok(mif)
+	}
+	/// Extracts string table of byte strings delimited by `delimiter`.
+	///
+	/// Inverse of `from_strings()`, all words must belong to the table.
+	pub fn strings(&self, delimiter: Delimiter<T>) -> Result<Vec<Vec<u8>>> {
+		let mut words = self.expand().enumerate();
+		let mut strings = Vec::new();
+		let byte = |(addr, word): (usize, T)|
+			word.to_u8().ok_or(ValueOutOfWidth(addr, 8));
+		match delimiter {
+			Delimiter::Terminator(terminator) => {
+				let mut string = Vec::new();
+				for (addr, word) in words {
+					if word == terminator {
+						strings.push(take(&mut string));
+					} else {
+						string.push(byte((addr, word))?);
+					}
+				}
+				if !string.is_empty() {
+					Err(MissingSentinel)?;
+				}
+			},
+			Delimiter::Prefix => while let Some((_, word)) = words.next() {
+				let len = word.to_usize().unwrap_or(usize::MAX);
+				let string = words.by_ref().take(len).map(byte)
+					.collect::<Result<Vec<u8>>>()?;
+				if string.len() < len {
+					Err(MissingWords(len - string.len()))?;
+				}
+				strings.push(string);
+			},
+		}
+		Ok(strings)
+	}
+	/// Reads `depth` LSB/MSB-`first` words from `bytes` reader.
+	///
+	/// Errors on words exceeding `width()`, see `read_truncated()` otherwise.
+	pub fn read(&mut self, bytes: &mut dyn Read, depth: usize, first: First)
+	-> Result<()> {
+		self.read_truncated(bytes, depth, first, Truncate::Error)
+	}
+	/// Reads `depth` LSB/MSB-`first` words from `bytes` reader truncating
+	/// words exceeding `width()` according to `policy`.
+	pub fn read_truncated(
+		&mut self,
+		bytes: &mut dyn Read,
+		depth: usize,
+		first: First,
+		policy: Truncate,
+	) -> Result<()> {
+		let first = first.resolve();
+		let align = self.align();
+		let mask = u128::MAX >> (128 - self.width);
+		let pads = align * 8 - self.width;
+		let mut words = 0;
+		for _ in 0..depth {
+			let word = match first {
+				Msb => bytes.read_uint128::<BE>(align),
+				_ => bytes.read_uint128::<LE>(align),
+			}?;
+			let word = match policy {
+				Truncate::Error => word,
+				Truncate::MaskLow => word & mask,
+				Truncate::MaskHigh => word >> pads,
+			};
+			self.push_word(T::from_u128(word)
+				.ok_or(ValueOutOfWidth(words, self.width))?)?;
+			words += 1;
+		}
+		if depth != words {
+			Err(MissingWords(depth - words))?;
+		}
+		Ok(())
+	}
+	/// Reads `depth` LSB/MSB-`first` words from `bytes` reader reassembling
+	/// their bits according to field `map`.
+	///
+	/// The `map` lists the source bit of every bit of a word from its
+	/// least-significant bit on, that is bit `i` of a word is bit `map[i]` of
+	/// the `align()` bytes read. Covers byte-swaps, bit-reversals, and
+	/// nibble-swaps as special cases. Errors if `map` is not of `width()`
+	/// distinct source bits within `align()` bytes. Unmapped source bits are
+	/// ignored.
+	pub fn read_mapped(
+		&mut self,
+		bytes: &mut dyn Read,
+		depth: usize,
+		first: First,
+		map: &[usize],
+	) -> Result<()> {
+		let align = self.align();
+		let mut bits = map.to_vec();
+		bits.sort_unstable();
+		bits.dedup();
+		if map.len() != self.width || bits.len() != map.len()
+			|| bits.last().is_some_and(|&bit| bit >= align * 8) {
+			Err(InvalidFieldMap(map.len(), self.width))?;
+		}
+		let first = first.resolve();
+		for _ in 0..depth {
+			let word = match first {
+				Msb => bytes.read_uint128::<BE>(align),
+				_ => bytes.read_uint128::<LE>(align),
+			}?;
+			let word = map.iter().enumerate()
+				.fold(0, |sum, (bit, &from)| sum | (word >> from & 1) << bit);
+			self.push_word(T::from_u128(word).unwrap())?;
+		}
+		Ok(())
+	}
+	/// Reads `depth` mixed-endian words from `bytes` reader of groups of
+	/// `group` bytes each, LSB/MSB-`first` within and LSB/MSB-`groups` first
+	/// across groups.
+	///
+	/// Covers word-swapped layouts as in 32-bit words of two 16-bit halves
+	/// with `group` of 2, `first` of `Lsb`, and `groups` of `Msb`. Errors if
+	/// `group` is zero or no divisor of `align()`.
+	pub fn read_grouped(
+		&mut self,
+		bytes: &mut dyn Read,
+		depth: usize,
+		first: First,
+		group: usize,
+		groups: First,
+	) -> Result<()> {
+		let align = self.align();
+		if group == 0 || !align.is_multiple_of(group) {
+			Err(InvalidGroup(group, align))?;
+		}
+		let (first, groups) = (first.resolve(), groups.resolve());
+		let count = align / group;
+		let mut buffer = [0; 16];
+		for _ in 0..depth {
+			bytes.read_exact(&mut buffer[..align])?;
+			let word = buffer[..align].chunks(group).enumerate()
+				.fold(0, |word, (index, chunk)| {
+					let part = match first {
+						Msb => BE::read_uint128(chunk, group),
+						_ => LE::read_uint128(chunk, group),
+					};
+					let index = if groups == Msb { count - 1 - index } else {
+						index
+					};
+					word | part << (index * group * 8)
+				});
+			self.push_word(T::from_u128(word)
+				.ok_or(ValueOutOfWidth(self.depth, self.width))?)?;
+		}
+		Ok(())
+	}
+	/// Reads LSB/MSB-`first` words from `bytes` reader until `sentinel` word.
+	///
+	/// Pushes the `sentinel` word as well if `include` is `true`. Returns the
+	/// count of words read including the `sentinel` word.
+	pub fn read_until(
+		&mut self,
+		bytes: &mut dyn Read,
+		sentinel: T,
+		first: First,
+		include: bool,
+	) -> Result<usize> {
+		let first = first.resolve();
+		let align = self.align();
+		let mut words = 0;
+		loop {
+			let word = match first {
+				Msb => bytes.read_uint128::<BE>(align),
+				_ => bytes.read_uint128::<LE>(align),
+			}.map_err(|err| match err.kind() {
+				ErrorKind::UnexpectedEof => MissingSentinel,
+				_ => IoError(err),
+			})?;
+			let word = T::from_u128(word)
+				.ok_or(ValueOutOfWidth(self.depth, self.width))?;
+			words += 1;
+			if word != sentinel || include {
+				self.push_word(word)?;
+			}
+			if word == sentinel {
+				return Ok(words);
+			}
+		}
+	}
+	/// Reads LSB/MSB-`first` words from `bytes` reader until its end without
+	/// knowing the depth beforehand, see `read()` otherwise.
+	///
+	/// Returns the count of words read. Errors with `MissingWords` if the end
+	/// is reached within a word.
+	pub fn extend_from_reader(&mut self, bytes: &mut dyn Read, first: First)
+	-> Result<usize> {
+		let first = first.resolve();
+		let align = self.align();
+		let mut buffer = [0; 16];
+		let mut words = 0;
+		loop {
+			let mut read = 0;
+			while read < align {
+				match bytes.read(&mut buffer[read..align]) {
+					Ok(0) => break,
+					Ok(count) => read += count,
+					Err(err) if err.kind() == ErrorKind::Interrupted => {},
+					Err(err) => Err(err)?,
+				}
+			}
+			if read == 0 {
+				return Ok(words);
+			}
+			if read < align {
+				Err(MissingWords(1))?;
+			}
+			let word = match first {
+				Msb => BE::read_uint128(&buffer[..align], align),
+				_ => LE::read_uint128(&buffer[..align], align),
+			};
+			self.push_word(T::from_u128(word)
+				.ok_or(ValueOutOfWidth(self.depth, self.width))?)?;
+			words += 1;
+		}
+	}
+	/// Reads `depth` bit-packed LSB/MSB-`first` words from `bytes` reader.
+	///
+	/// Words are packed back-to-back without padding, consuming `width()`
+	/// bits per word and `ceil(depth * width() / 8)` bytes in total. With
+	/// `Lsb`, bits are consumed from the least-significant bit of each byte on
+	/// and fill words from their least-significant bit on, vice versa with
+	/// `Msb`. Unused bits of the last byte are ignored.
+	pub fn read_packed(
+		&mut self,
+		bytes: &mut dyn Read,
+		depth: usize,
+		first: First,
+	) -> Result<()> {
+		let first = first.resolve();
+		let mut byte = 0u8;
+		let mut left = 0;
+		for words in 0..depth {
+			let mut word = 0u128;
+			let mut bits = 0;
+			while bits < self.width {
+				if left == 0 {
+					byte = bytes.read_u8()?;
+					left = 8;
+				}
+				let take = left.min(self.width - bits);
+				let mask = (1u16 << take) as u128 - 1;
+				match first {
+					Msb => word = word << take
+						| (byte >> (left - take)) as u128 & mask,
+					_ => word |= ((byte >> (8 - left)) as u128 & mask) << bits,
+				}
+				bits += take;
+				left -= take;
+			}
+			self.push_word(T::from_u128(word)
+				.ok_or(ValueOutOfWidth(words, self.width))?)?;
+		}
+		Ok(())
+	}
+	/// Size of MIF text in bytes as written with `style`, see `write()`.
+	pub fn size_bytes(&self, style: &Style) -> Result<usize> {
+		let mut count = Count(0);
+		self.write(&mut count, style)?;
+		Ok(count.0)
+	}
+	/// Writes MIF to writer.
+	///
+	///   * `lines`: Writer, MIF is written to.
+	///   * `style`: How to write MIF, see `Style`.
+	pub fn write(&self, lines: &mut dyn Write, style: &Style) -> Result<()> {
+		if style.and_mask.is_some() || style.or_mask.is_some() {
+			let mif = self.masked(style.and_mask, style.or_mask);
+			let style = Style { and_mask: None, or_mask: None, ..*style };
+			return mif.write(lines, &style);
+		}
+		if style.dialect == Dialect::Gowin {
+			return self.write_gowin(lines, style);
+		}
+		let step = style.addr_step.unwrap_or(1).max(1);
+		let min_pads = self.addr_pads(style.origin, step)?;
+		let addr_pads = match style.digits {
+			Some(digits) if digits < min_pads =>
+				Err(FewerDigits(digits, min_pads))?,
+			Some(digits) => digits,
+			None => min_pads,
+		};
+		let word_pads = self.word_pads();
+		let hex = |value, pads| Hex(value, pads, style.case);
+		let at = |addr: usize| {
+			let addr = (addr * step) as i128 + style.origin as i128;
+			let sign = if addr < 0 { "-" } else { "" };
+			format!("{}{}", sign, hex(addr.unsigned_abs(), addr_pads))
+		};
+		let mut comments = Vec::new();
+		if style.sources {
+			comments.extend(self.notes.iter().cloned());
+		}
+		let mut marks = Vec::new();
+		if style.areas {
+			if style.inline { &mut marks } else { &mut comments }
+				.extend(self.areas.iter()
+				.map(|(addr, path)| (*addr, path.display().to_string())));
+		}
+		if let Some(block) = style.blocks {
+			let sums = self.block_checksums(block);
+			let ends = sums.iter().skip(1).map(|&(addr, _)| addr)
+				.chain(once(self.depth));
+			comments.extend(sums.iter().zip(ends).map(|(&(addr, crc), end)|
+				(addr, format!("CRC-32 {} of {} words",
+					hex(crc as u128, 8), end - addr))));
+		}
+		let mut top = Vec::new();
+		if style.version.is_some() || style.first.is_some()
+			|| !comments.is_empty() {
+			if let Some(version) = style.version {
+				writeln!(top, "-- generated by mif {}", version)?;
+			}
+			if let Some(first) = style.first {
+				writeln!(top, "-- FIRST={}", match first.resolve() {
+					Msb => "msb",
+					_ => "lsb",
+				})?;
+			}
+			comments.sort_by_key(|&(addr, _)| addr);
+			for (addr, comment) in comments {
+				writeln!(top, "-- {}: {}", at(addr), comment)?;
+			}
+			if !style.compact {
+				writeln!(top)?;
+			}
+		}
+		let generic = style.dialect == Dialect::Generic;
+		let header_first = style.header_first || generic;
+		if !header_first {
+			lines.write_all(&top)?;
+		}
+		let blank = if style.compact { "" } else { "\n" };
+		let keywords = |statement: String| match style.keywords {
+			KeywordCase::Upper => statement,
+			KeywordCase::Lower => statement.to_ascii_lowercase(),
+		};
+		write!(lines, "{}{}", keywords(format!("\
+			WIDTH={};\n\
+			DEPTH={};\n{}\
+			ADDRESS_RADIX=HEX;\n\
+			DATA_RADIX=HEX;\n", self.width, self.depth, blank)), blank)?;
+		if header_first {
+			lines.write_all(&top)?;
+		}
+		writeln!(lines, "{}", keywords("CONTENT BEGIN".into()))?;
+		let (indent, colon) =
+			if generic { ("", " : ") } else { ("\t", "  :   ") };
+		let min_run = if style.single || generic {
+			Some(usize::MAX)
+		} else {
+			style.min_run
+		};
+		let lhs_pads = if style.align {
+			let addr_pads = addr_pads + usize::from(style.origin < 0);
+			if self.runs(min_run).any(|(_word, bulk)| bulk > 1) {
+				2 * addr_pads + 4
+			} else {
+				addr_pads
+			}
+		} else {
+			0
+		};
+		let mut addr = 0;
+		let mut marks = marks.into_iter().peekable();
+		for (word, bulk) in self.runs(min_run) {
+			let end = addr + bulk;
+			let word = hex(word.to_u128().unwrap(), word_pads);
+			while addr < end {
+				while let Some((_, comment)) =
+					marks.next_if(|&(mark, _)| mark <= addr) {
+					writeln!(lines, "{}-- {}: {}", indent, at(addr), comment)?;
+				}
+				let bulk = marks.peek()
+					.map_or(end, |&(mark, _)| mark.min(end)) - addr;
+				let lhs = if bulk == 1 {
+					at(addr)
+				} else {
+					format!("[{}..{}]", at(addr), at(addr + bulk - 1))
+				};
+				write!(lines, "{}{:pads$}{}{};", indent, lhs, colon, word,
+					pads = lhs_pads)?;
+				if style.sources {
+					let mut paths = self.sources(addr, bulk)
+						.map(|path| path.display());
+					if let Some(path) = paths.next() {
+						write!(lines, " -- from {}", path)?;
+						paths.try_for_each(|path|
+							write!(lines, ", {}", path))?;
+					}
+				}
+				writeln!(lines)?;
+				addr += bulk;
+			}
+		}
+		for (mark, comment) in marks {
+			writeln!(lines, "{}-- {}: {}", indent, at(mark), comment)?;
+		}
+		writeln!(lines, "{}", keywords("END;".into()))?;
+		Ok(())
+	}
+	/// Writes Gowin memory initialization file, see `Dialect::Gowin`.
+	///
+	/// Errors if `style` has options given other than `case` and the masks.
+	fn write_gowin(&self, lines: &mut dyn Write, style: &Style) -> Result<()> {
+		let rest = Style {
+			case: HexCase::default(),
+			dialect: Dialect::default(),
+			..*style
+		};
+		if rest != Style::default() {
+			Err(UnsupportedStyle("Gowin"))?;
+		}
+		writeln!(lines, "\
+			#File_format=Hex\n\
+			#Memory_format=Hex\n\
+			#Address_depth={}\n\
+			#Data_width={}", self.depth, self.width)?;
+		let word_pads = self.word_pads();
+		for word in self.expand() {
+			writeln!(lines, "{}",
+				Hex(word.to_u128().unwrap(), word_pads, style.case))?;
+		}
+		Ok(())
+	}
+	/// Copy with words ANDed with `and_mask` and then ORed with `or_mask`.
+	fn masked(&self, and_mask: Option<u128>, or_mask: Option<u128>) -> Self {
+		let max_value = self.max_value().to_u128().unwrap();
+		let and_mask = and_mask.unwrap_or(max_value);
+		let or_mask = or_mask.unwrap_or(0);
+		let mut mif = Mif { words: Vec::new(), depth: 0, ..self.clone() };
+		for &(word, bulk) in &self.words {
+			let word = word.to_u128().unwrap() & and_mask | or_mask;
+			let word = T::from_u128(word & max_value).unwrap();
+			mif.push(word, bulk).unwrap();
+		}
+		mif
+	}
+	/// MIF as string, see `write()`.
+	pub fn to_string_mif(&self, style: &Style) -> Result<String> {
+		let mut lines = Vec::new();
+		self.write(&mut lines, style)?;
+		Ok(String::from_utf8(lines).unwrap())
+	}
+}
+
+/// Implements `TryFrom` between MIFs of distinct word types via `convert()`.
+macro_rules! try_from {
+	($($from:ty => $($into:ty),+;)+) => {$($(
+		impl TryFrom<Mif<$from>> for Mif<$into> {
+			type Error = Error;
+
+			fn try_from(mif: Mif<$from>) -> Result<Self> {
+				mif.convert()
+			}
+		}
+	)+)+};
+}
+
+try_from! {
+	u8 => u16, u32, u64, u128;
+	u16 => u8, u32, u64, u128;
+	u32 => u8, u16, u64, u128;
+	u64 => u8, u16, u32, u128;
+	u128 => u8, u16, u32, u64;
+}
+
+/// How to write MIF.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub struct Style {
+	/// Whether to comment memory areas as in `-- 0000: name.bin`.
+	pub areas: bool,
+	/// Whether to comment memory areas inline within `CONTENT` at the
+	/// addresses they begin instead of on top, splitting runs spanning them.
+	///
+	/// Split runs are rejected by `Mif::parse()` in strict mode.
+	pub inline: bool,
+	/// Whether to comment runs with their memory areas as in `-- from name.bin`
+	/// and to comment notes, see `Mif::note()`.
+	pub sources: bool,
+	/// Case of hexadecimal digits, applying to COE, `$readmemh()`, VHDL, and C
+	/// output as well.
+	pub case: HexCase,
+	/// Case of keywords as in `WIDTH` or `CONTENT BEGIN`, uppercase by default.
+	pub keywords: KeywordCase,
+	/// Block size in words to comment CRC-32 of blocks as in
+	/// `-- 0400: CRC-32 CBF43926 of 1024 words`, see `Mif::block_checksums()`.
+	pub blocks: Option<NonZeroUsize>,
+	/// Whether to omit the blank lines between header sections.
+	pub compact: bool,
+	/// Optional version of generator stamped on top as in
+	/// `-- generated by mif 0.3.0`.
+	///
+	/// Off by default as stamping breaks byte-identical output across versions.
+	pub version: Option<&'static str>,
+	/// Whether to write the comments on top after the `WIDTH`, `DEPTH`, and
+	/// radix header instead of before it, for parsers requiring the header
+	/// first. Always the case for `Dialect::Generic`.
+	pub header_first: bool,
+	/// Whether to align the colons and words of all lines by padding single
+	/// addresses to the width of ranges of addresses as in `[0..F]` if any.
+	pub align: bool,
+	/// Whether to write every address on its own line instead of as ranges
+	/// like `[0..F]`, possibly blowing up the size by the words' `bulk`.
+	pub single: bool,
+	/// Optional minimum count of words of a run written as range of addresses
+	/// like `[0..F]`, two by default, whereas shorter runs are written as
+	/// single addresses, see `single` to write no ranges at all.
+	///
+	/// Applies to MIF and VHDL output and marks runs of `$readmemh()` output
+	/// by their address as in `@10` if given, as other formats expand runs
+	/// anyway.
+	pub min_run: Option<usize>,
+	/// MIF dialect of vendor tools, see `Dialect`.
+	pub dialect: Dialect,
+	/// Optional fixed count of address digits instead of the fewest required
+	/// by the depth, aligning addresses across MIFs of different depths.
+	pub digits: Option<usize>,
+	/// Optional LSB/MSB first the words have been read with, commented as in
+	/// `-- FIRST=lsb` to default it when exporting bytes, see `parse::first()`.
+	pub first: Option<First>,
+	/// Signed origin added to addresses as written, zero by default.
+	///
+	/// Unusual and display-only as MIF addresses cannot be negative, but some
+	/// memory maps document addresses relative to an origin as in `-0010`.
+	pub origin: isize,
+	/// Optional stride of addresses per word as written, one by default and
+	/// if zero.
+	///
+	/// Addresses memories organized as words by bytes if set to `align()`,
+	/// stepping single addresses and both ends of ranges as in `[0..C]` for
+	/// four 32-bit words. Display-only as `DEPTH` still counts words. Errors
+	/// if stepped addresses exceed the address space.
+	pub addr_step: Option<usize>,
+	/// Optional count of data bytes per Intel HEX or S-record, 16 by default.
+	///
+	/// Ranges from 1 through 255 and for S-records further to the bytes left
+	/// by their address size, that is 252 of `S1`, 251 of `S2`, and 250 of
+	/// `S3` records, erroring otherwise.
+	pub record_bytes: Option<usize>,
+	/// Optional mask ANDed with words as written, ignoring status bits say.
+	///
+	/// Display-only as masked words cannot be read back as their originals.
+	pub and_mask: Option<u128>,
+	/// Optional mask ORed with words as written after the `and_mask`, bits
+	/// beyond the word width are ignored.
+	///
+	/// Display-only as masked words cannot be read back as their originals.
+	pub or_mask: Option<u128>,
+}
+
+/// Region of addresses at which words of two MIFs differ, see
+/// `Mif::diff_runs()`.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct RunDiff<T> {
+	/// First address of region.
+	pub start: usize,
+	/// Address after region.
+	pub end: usize,
+	/// Word of first MIF or `None` beyond its depth.
+	pub a_word: Option<T>,
+	/// Word of second MIF or `None` beyond its depth.
+	pub b_word: Option<T>,
+}
+
+/// Delimiter of strings in string table, see `Mif::from_strings()`.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Delimiter<T> {
+	/// Terminates every string with given word as in null-terminated strings.
+	Terminator(T),
+	/// Prefixes every string with its length in bytes as a word.
+	Prefix,
+}
+
+/// Truncation policy of words exceeding width, see `Mif::read_truncated()`.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub enum Truncate {
+	/// Errors with `ValueOutOfWidth`.
+	#[default]
+	Error,
+	/// Keeps the low bits, that is the high padding bits are masked off.
+	MaskLow,
+	/// Keeps the high bits of the word's bytes, that is the low padding bits
+	/// are shifted off.
+	MaskHigh,
+}
+
+/// Case of hexadecimal digits.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub enum HexCase {
+	/// Uppercase hexadecimal digits as in `0xABCD`.
+	#[default]
+	Upper,
+	/// Lowercase hexadecimal digits as in `0xabcd`.
+	Lower,
+}
+
+/// Case of MIF keywords including their values as in `ADDRESS_RADIX=HEX;`.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub enum KeywordCase {
+	/// Uppercase keywords as in `CONTENT BEGIN`.
+	#[default]
+	Upper,
+	/// Lowercase keywords as in `content begin` for case-sensitive parsers.
+	Lower,
+}
+
+/// MIF dialect of vendor tools, see `Style`.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub enum Dialect {
+	/// Intel Quartus Prime MIF with tab-indented and aligned runs as in
+	/// `[0..F]  :   0000;`.
+	#[default]
+	Quartus,
+	/// Plain MIF without ranges and alignment as in `0 : 0000;`, writing every
+	/// address on its own line and the header first for tools not supporting
+	/// ranges.
+	Generic,
+	/// Gowin memory initialization file of `#` header lines followed by one
+	/// word per line without addresses. Supports no style options but the
+	/// case of hexadecimal digits and the masks, erroring otherwise.
+	Gowin,
+}
+
+impl FromStr for Dialect {
+	type Err = Error;
+
+	fn from_str(from: &str) -> Result<Self> {
+		match from {
+			"quartus" => Ok(Dialect::Quartus),
+			"generic" => Ok(Dialect::Generic),
+			"gowin" => Ok(Dialect::Gowin),
+			_ => Err(UnknownDialect),
+		}
+	}
+}
+
+/// Writer counting bytes written.
+struct Count(usize);
+
+impl Write for Count {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.0 += buf.len();
+		Ok(buf.len())
+	}
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+/// Hexadecimal value padded to digits in given case.
+struct Hex(u128, usize, HexCase);
+
+impl Display for Hex {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self.2 {
+			HexCase::Upper => write!(f, "{:01$X}", self.0, self.1),
+			HexCase::Lower => write!(f, "{:01$x}", self.0, self.1),
+		}
+	}
+}
+
+/// LSB/MSB first (little/big-endian).
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+#[cfg_attr(feature = "cli", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "cli", serde(rename_all = "kebab-case"))]
+pub enum First {
+	/// Least-significant byte first (little-endian).
+	#[default]
+	Lsb,
 	/// Most-significant byte first (big-endian).
 	Msb,
+	/// Host byte order, see `resolve()`.
+	Native,
 }
 
-impl Default for First {
-	fn default() -> Self { Lsb }
+impl First {
+	/// Resolves `Native` to `Lsb` or `Msb` depending on host byte order.
+	///
+	/// Words are read and written with the resolved byte order. Hence, bytes
+	/// read with `Native` on a little-endian host are written with `Lsb`.
+	pub fn resolve(self) -> First {
+		match self {
+			First::Native if cfg!(target_endian = "big") => Msb,
+			First::Native => Lsb,
+			first => first,
+		}
+	}
+	/// Detects LSB/MSB first from byte order mark as in UTF-16.
+	///
+	/// Returns `Lsb` if `header` starts with `[0xff, 0xfe]`, `Msb` if it starts
+	/// with `[0xfe, 0xff]`, and `None` otherwise.
+	pub fn detect(header: &[u8]) -> Option<First> {
+		match header {
+			[0xff, 0xfe, ..] => Some(Lsb),
+			[0xfe, 0xff, ..] => Some(Msb),
+			_ => None,
+		}
+	}
+	/// Guesses LSB/MSB first of `bytes` as words of `width` bits.
+	///
+	/// This is a heuristic choosing the byte order resulting in less and hence
+	/// longer runs of equal words, preferring `Lsb` on par. Trailing bytes not
+	/// making up a whole word are ignored.
+	pub fn guess(bytes: &[u8], width: usize) -> Result<First> {
+		let mut lsb = Mif::<u128>::new(width)?;
+		let mut msb = lsb.clone();
+		let depth = bytes.len() / lsb.align();
+		lsb.read(&mut &bytes[..], depth, Lsb)?;
+		msb.read(&mut &bytes[..], depth, Msb)?;
+		Ok(if msb.words().len() < lsb.words().len() { Msb } else { Lsb })
+	}
 }
 
 impl FromStr for First {
@@ -348,10 +2109,1152 @@ impl FromStr for First {
 		match from {
 			"lsb" => Ok(Lsb),
 			"msb" => Ok(Msb),
+			"native" => Ok(First::Native),
 			_ => Err(NeitherLsbNorMsbFirst),
 		}
 	}
 }
 
+/// Output format.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub enum Format {
+	/// Memory Initialization File.
+	#[default]
+	Mif,
+	/// Intel HEX.
+	Ihex,
+	/// Motorola S-record.
+	Srec,
+	/// Xilinx coefficient file.
+	Coe,
+	/// Verilog `$readmemh()` file.
+	Readmemh,
+	/// VHDL constant array.
+	Vhdl,
+	/// C header array, see `Mif::write_c_header()`.
+	C,
+}
+
+impl FromStr for Format {
+	type Err = Error;
+
+	fn from_str(from: &str) -> Result<Self> {
+		match from {
+			"mif" => Ok(Format::Mif),
+			"ihex" => Ok(Format::Ihex),
+			"srec" => Ok(Format::Srec),
+			"coe" => Ok(Format::Coe),
+			"readmemh" => Ok(Format::Readmemh),
+			"vhdl" => Ok(Format::Vhdl),
+			"c" => Ok(Format::C),
+			_ => Err(UnknownFormat),
+		}
+	}
+}
+
+impl Format {
+	/// Format selected by the extension of `path`.
+	///
+	/// Selects `Ihex` for `.hex` and `.ihex`, `Srec` for `.srec`, `.s19`,
+	/// `.s28`, and `.s37`, `Coe` for `.coe`, `Vhdl` for `.vhd` and `.vhdl`,
+	/// and `C` for `.h`, case-insensitively. Defaults to `Mif` for any other
+	/// extension.
+	pub fn from_path(path: &Path) -> Self {
+		let extension = path.extension()
+			.and_then(|extension| extension.to_str())
+			.map(str::to_ascii_lowercase).unwrap_or_default();
+		match extension.as_str() {
+			"hex" | "ihex" => Format::Ihex,
+			"srec" | "s19" | "s28" | "s37" => Format::Srec,
+			"coe" => Format::Coe,
+			"vhd" | "vhdl" => Format::Vhdl,
+			"h" => Format::C,
+			_ => Format::Mif,
+		}
+	}
+}
+
+/// Trailing checksum scheme of ROM footers, see `Mif::trailing_checksum()`.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Footer {
+	/// Two's complement of the sum of words making the sum of all words zero.
+	SumToZero,
+	/// Ones' complement of the sum of words making the sum of all words ones.
+	SumComplement,
+	/// CRC-16/CCITT-FALSE of the words as `align()` bytes LSB first each, see
+	/// `crc16()`.
+	Crc16,
+}
+
+/// Error-correcting code or parity scheme, see `Mif::with_parity()`.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Ecc {
+	/// One check bit making the count of set bits even.
+	EvenParity,
+	/// One check bit making the count of set bits odd.
+	OddParity,
+	/// Hamming code of `n` bits in total of which `k` data bits.
+	///
+	/// Data bits from the least-significant one on occupy the one-based code
+	/// positions which are no power of two, check bit `i` covers the positions
+	/// with bit `i` set. The `n - k` check bits must cover `n` positions and
+	/// `k` must equal the word width.
+	Hamming(usize, usize),
+}
+
+impl Ecc {
+	/// Count of check bits for words of `width` bits.
+	pub fn checks(self, width: usize) -> Result<usize> {
+		match self {
+			Ecc::EvenParity | Ecc::OddParity => Ok(1),
+			Ecc::Hamming(n, k) => {
+				let r = n.saturating_sub(k);
+				if k != width || r == 0 || r >= 128
+				|| r < usize::BITS as usize && n >= 1 << r {
+					Err(InvalidHamming(n, k))
+				} else {
+					Ok(r)
+				}
+			},
+		}
+	}
+	/// Check bits of `word`.
+	fn check(self, word: u128) -> u128 {
+		match self {
+			Ecc::EvenParity => (word.count_ones() & 1) as u128,
+			Ecc::OddParity => (!word.count_ones() & 1) as u128,
+			Ecc::Hamming(n, _) => (1..=n)
+				.filter(|pos| !pos.is_power_of_two()).enumerate()
+				.filter(|&(bit, _pos)| word >> bit & 1 == 1)
+				.fold(0, |check, (_bit, pos)| check ^ pos as u128),
+		}
+	}
+}
+
+impl FromStr for Ecc {
+	type Err = Error;
+
+	/// Parses `"even"`, `"odd"`, or `"hamming-n-k"` as in `"hamming-12-8"`.
+	fn from_str(from: &str) -> Result<Self> {
+		match from {
+			"even" => Ok(Ecc::EvenParity),
+			"odd" => Ok(Ecc::OddParity),
+			_ => {
+				let mut codes = from.strip_prefix("hamming-")
+					.ok_or(UnknownEcc)?.splitn(2, '-')
+					.map(|code| code.parse().map_err(|_| UnknownEcc));
+				let n = codes.next().ok_or(UnknownEcc)??;
+				let k = codes.next().ok_or(UnknownEcc)??;
+				Ok(Ecc::Hamming(n, k))
+			},
+		}
+	}
+}
+
+/// CRC-32 (IEEE 802.3) of `bytes` continuing `crc`, zero at first.
+pub fn crc32(crc: u32, bytes: &[u8]) -> u32 {
+	let mut crc = !crc;
+	for &byte in bytes {
+		crc ^= byte as u32;
+		for _ in 0..8 {
+			crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+		}
+	}
+	!crc
+}
+
+/// CRC-16/CCITT-FALSE of `bytes` continuing `crc`, `0xffff` at first.
+pub fn crc16(crc: u16, bytes: &[u8]) -> u16 {
+	let mut crc = crc;
+	for &byte in bytes {
+		crc ^= (byte as u16) << 8;
+		for _ in 0..8 {
+			crc = (crc << 1) ^ (0x1021 & (crc >> 15).wrapping_neg());
+		}
+	}
+	crc
+}
+
 /// Default width of 16 bits.
 pub const fn default_width() -> usize { 16 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn uniform_and_fill_word() {
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		assert_eq!(mif.is_uniform(), None);
+		assert_eq!(mif.fill_word(), None);
+		mif.push(0xff, 4).unwrap();
+		assert_eq!(mif.is_uniform(), Some(0xff));
+		assert_eq!(mif.fill_word(), Some(0xff));
+		mif.push(0x00, 2).unwrap();
+		mif.push(0xff, 1).unwrap();
+		mif.push(0x01, 4).unwrap();
+		assert_eq!(mif.is_uniform(), None);
+		assert_eq!(mif.fill_word(), Some(0xff));
+		mif.push(0x00, 3).unwrap();
+		assert_eq!(mif.fill_word(), Some(0x00));
+	}
+
+	#[test]
+	fn narrow_to_fitting_width() {
+		let mut mif = Mif::<u16>::new(16).unwrap();
+		mif.push(0x00, 3).unwrap();
+		mif.push(0x1f, 1).unwrap();
+		assert!(matches!(mif.clone().narrow(4), Err(ValueOutOfWidth(3, 4))));
+		assert!(matches!(mif.clone().narrow(17), Err(WiderWidth(17, 16))));
+		assert!(matches!(mif.clone().narrow(0),
+			Err(WidthOutOfRange(0, 16, _))));
+		mif.narrow(5).unwrap();
+		assert_eq!(mif.width(), 5);
+		assert_eq!(mif.words(), &vec![(0x00, 3), (0x1f, 1)]);
+	}
+
+	#[test]
+	fn lowercase_mif() {
+		let mut mif = Mif::<u16>::new(12).unwrap();
+		mif.area("a.bin".into());
+		mif.push(0xabc, 1).unwrap();
+		mif.push(0xdef, 26).unwrap();
+		let style = Style {
+			areas: true,
+			case: HexCase::Lower,
+			..Style::default()
+		};
+		assert_eq!(mif.to_string_mif(&style).unwrap(), "\
+			-- 00: a.bin\n\
+			\n\
+			WIDTH=12;\n\
+			DEPTH=27;\n\
+			\n\
+			ADDRESS_RADIX=HEX;\n\
+			DATA_RADIX=HEX;\n\
+			\n\
+			CONTENT BEGIN\n\
+			\t00  :   abc;\n\
+			\t[01..1a]  :   def;\n\
+			END;\n");
+	}
+
+	#[test]
+	fn push_and_join_bounded() {
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		assert_eq!(mif.push_bounded(0x11, 3, 8).unwrap(), 5);
+		assert!(matches!(mif.push_bounded(0x22, 6, 8),
+			Err(DepthExceeded(9, 8))));
+		assert_eq!(mif.depth(), 3);
+		assert_eq!(mif.push_bounded(0x22, 5, 8).unwrap(), 0);
+		let other = mif.clone();
+		assert!(matches!(mif.join_bounded(&other, 15),
+			Err(DepthExceeded(16, 15))));
+		assert_eq!(mif.join_bounded(&other, 16).unwrap(), 0);
+		assert_eq!(mif.words(), &vec![(0x11, 3), (0x22, 5), (0x11, 3),
+			(0x22, 5)]);
+	}
+
+	#[test]
+	fn depth_bytes_of_odd_widths() {
+		for &(width, align) in &[(1, 1), (8, 1), (9, 2), (12, 2), (17, 3),
+			(33, 5), (127, 16)] {
+			let mut mif = Mif::<u128>::new(width).unwrap();
+			mif.push(0, 3).unwrap();
+			assert_eq!(mif.align(), align);
+			assert_eq!(mif.depth_bytes(), 3 * align);
+			assert!(mif.fits_in(3 * align));
+			assert!(!mif.fits_in(3 * align - 1));
+		}
+		let mif = Mif::<u8>::new(8).unwrap();
+		assert_eq!(mif.depth_bytes(), 0);
+		assert!(mif.fits_in(0));
+		assert!(mif.size_bytes(&Style::default()).unwrap()
+			> mif.depth_bytes());
+	}
+
+	#[test]
+	fn block_checksums_with_partial_block() {
+		let mut mif = Mif::<u16>::new(16).unwrap();
+		mif.push_slice(&[0x3231, 0x3433, 0x3635, 0x3837, 0x0039]).unwrap();
+		let block = |block| NonZeroUsize::new(block).unwrap();
+		assert_eq!(mif.block_checksums(block(2)), vec![
+			(0, crc32(0, b"1234")),
+			(2, crc32(0, b"5678")),
+			(4, crc32(0, b"9\0")),
+		]);
+		assert_eq!(mif.block_checksums(block(5)),
+			vec![(0, crc32(0, b"123456789\0"))]);
+		assert_eq!(mif.block_checksums(block(9)),
+			mif.block_checksums(block(5)));
+		let empty = Mif::<u8>::new(8).unwrap();
+		assert!(empty.block_checksums(block(1)).is_empty());
+		assert_eq!(crc32(0, b"123456789"), 0xcbf4_3926);
+	}
+
+	#[test]
+	fn compact_mif() {
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		mif.area("a.bin".into());
+		mif.push(0x01, 2).unwrap();
+		let areas = Style { areas: true, ..Style::default() };
+		let compact = Style { compact: true, ..areas };
+		assert_eq!(mif.to_string_mif(&areas).unwrap(), "\
+			-- 0: a.bin\n\
+			\n\
+			WIDTH=8;\n\
+			DEPTH=2;\n\
+			\n\
+			ADDRESS_RADIX=HEX;\n\
+			DATA_RADIX=HEX;\n\
+			\n\
+			CONTENT BEGIN\n\
+			\t[0..1]  :   01;\n\
+			END;\n");
+		assert_eq!(mif.to_string_mif(&compact).unwrap(), "\
+			-- 0: a.bin\n\
+			WIDTH=8;\n\
+			DEPTH=2;\n\
+			ADDRESS_RADIX=HEX;\n\
+			DATA_RADIX=HEX;\n\
+			CONTENT BEGIN\n\
+			\t[0..1]  :   01;\n\
+			END;\n");
+		assert_eq!(Mif::parse(&mif.to_string_mif(&compact).unwrap()).unwrap()
+			.words(), mif.words());
+	}
+
+	#[test]
+	fn retain_runs_shifting_addresses() {
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		mif.area("a.bin".into());
+		mif.push(0x00, 2).unwrap();
+		mif.area("b.bin".into());
+		mif.push(0xff, 3).unwrap();
+		mif.area("c.bin".into());
+		mif.push(0x00, 1).unwrap();
+		mif.note("skipped".into());
+		mif.push(0x11, 2).unwrap();
+		mif.push(0xff, 1).unwrap();
+		assert_eq!(mif.depth(), 9);
+		mif.retain_runs(|word, _bulk| word != 0xff);
+		assert_eq!(mif.depth(), 5);
+		assert_eq!(mif.words(), &vec![(0x00, 3), (0x11, 2)]);
+		assert_eq!(mif.areas(), &vec![(0, "a.bin".into()), (2, "b.bin".into()),
+			(2, "c.bin".into())]);
+		assert_eq!(mif.notes(), &vec![(3, "skipped".into())]);
+		mif.retain_runs(|_word, bulk| bulk > 2);
+		assert_eq!(mif.depth(), 3);
+		assert_eq!(mif.words(), &vec![(0x00, 3)]);
+		assert_eq!(mif.notes(), &vec![(3, "skipped".into())]);
+	}
+
+	#[test]
+	fn to_string_mif_as_written() {
+		let mut mif = Mif::<u16>::new(16).unwrap();
+		mif.area("a.bin".into());
+		mif.push(0x1234, 300).unwrap();
+		mif.push(0xabcd, 1).unwrap();
+		let style = Style { areas: true, ..Style::default() };
+		let mut lines = Vec::new();
+		mif.write(&mut lines, &style).unwrap();
+		assert_eq!(mif.to_string_mif(&style).unwrap().as_bytes(), &lines[..]);
+		let style = Style { digits: Some(2), ..style };
+		assert!(matches!(mif.to_string_mif(&style), Err(FewerDigits(2, 3))));
+	}
+
+	#[test]
+	fn parity_and_hamming_vectors() {
+		let mut mif = Mif::<u16>::new(4).unwrap();
+		mif.push(0b1101, 2).unwrap();
+		mif.push(0b0111, 1).unwrap();
+		let even = mif.with_parity(Ecc::EvenParity).unwrap();
+		assert_eq!(even.width(), 5);
+		assert_eq!(even.words(), &vec![(0b1_1101, 2), (0b1_0111, 1)]);
+		let odd = mif.parity(Ecc::OddParity).unwrap();
+		assert_eq!(odd.width(), 1);
+		assert_eq!(odd.words(), &vec![(0, 3)]);
+		let hamming = mif.with_parity(Ecc::Hamming(7, 4)).unwrap();
+		assert_eq!(hamming.width(), 7);
+		assert_eq!(hamming.words(), &vec![(0b010_1101, 2), (0b000_0111, 1)]);
+		let mut mif = Mif::<u16>::new(8).unwrap();
+		mif.push(0x01, 1).unwrap();
+		mif.push(0x80, 1).unwrap();
+		mif.push(0xff, 1).unwrap();
+		assert_eq!(mif.parity(Ecc::Hamming(12, 8)).unwrap().words(),
+			&vec![(0b0011, 1), (0b1100, 1), (0b0011, 1)]);
+		for (n, k) in [(7, 3), (7, 8), (8, 8), (8, 7)] {
+			assert!(matches!(mif.parity(Ecc::Hamming(n, k)),
+				Err(InvalidHamming(..))));
+		}
+	}
+
+	#[test]
+	fn address_width_bits_around_powers_of_two() {
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		assert_eq!(mif.address_width_bits(), 0);
+		mif.push(0, 1).unwrap();
+		assert_eq!(mif.address_width_bits(), 1);
+		for bits in 1..20 {
+			mif.push(0, (1 << bits) - mif.depth()).unwrap();
+			assert_eq!(mif.address_width_bits(), bits);
+			mif.push(0, 1).unwrap();
+			assert_eq!(mif.address_width_bits(), bits + 1);
+		}
+	}
+
+	#[test]
+	fn single_mif_without_runs() {
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		mif.push(0x11, 3).unwrap();
+		mif.push(0x22, 1).unwrap();
+		let style = Style { single: true, ..Style::default() };
+		let single = mif.to_string_mif(&style).unwrap();
+		assert!(!single.contains(".."));
+		assert!(single.ends_with("\
+			CONTENT BEGIN\n\
+			\t0  :   11;\n\
+			\t1  :   11;\n\
+			\t2  :   11;\n\
+			\t3  :   22;\n\
+			END;\n"));
+		assert_eq!(Mif::parse(&single).unwrap(), mif);
+	}
+
+	#[test]
+	fn overlay_within_across_and_at_end() {
+		let mut base = Mif::<u8>::new(8).unwrap();
+		base.push(0x11, 4).unwrap();
+		base.push(0x22, 4).unwrap();
+		let mut patch = Mif::<u8>::new(8).unwrap();
+		patch.push(0xff, 2).unwrap();
+		let mut mif = base.clone();
+		mif.overlay(&patch, 1).unwrap();
+		assert_eq!(mif.words(), &vec![(0x11, 1), (0xff, 2), (0x11, 1),
+			(0x22, 4)]);
+		let mut mif = base.clone();
+		mif.overlay(&patch, 3).unwrap();
+		assert_eq!(mif.words(), &vec![(0x11, 3), (0xff, 2), (0x22, 3)]);
+		let mut mif = base.clone();
+		mif.overlay(&patch, 6).unwrap();
+		assert_eq!(mif.words(), &vec![(0x11, 4), (0x22, 2), (0xff, 2)]);
+		assert_eq!(mif.depth(), 8);
+		let mut same = Mif::<u8>::new(8).unwrap();
+		same.push(0x22, 2).unwrap();
+		let mut mif = base.clone();
+		mif.overlay(&same, 4).unwrap();
+		assert_eq!(mif, base);
+		let mut mif = base.clone();
+		assert!(matches!(mif.overlay(&patch, 7), Err(DepthExceeded(9, 8))));
+		assert!(matches!(mif.overlay(&patch, usize::MAX),
+			Err(DepthExceeded(usize::MAX, 8))));
+		assert_eq!(mif, base);
+		mif.overlay_grow(&patch, 9).unwrap();
+		assert_eq!(mif.words(), &vec![(0x11, 4), (0x22, 4), (0x00, 1),
+			(0xff, 2)]);
+		assert_eq!(mif.depth(), 11);
+	}
+
+	#[test]
+	fn read_truncated_by_policy() {
+		let bytes = [0xcd, 0xab, 0x34, 0x02];
+		let read = |policy| {
+			let mut mif = Mif::<u16>::new(12).unwrap();
+			mif.read_truncated(&mut &bytes[..], 2, Lsb, policy).map(|()| mif)
+		};
+		assert!(matches!(read(Truncate::Error), Err(ValueOutOfWidth(0, 12))));
+		assert_eq!(read(Truncate::MaskLow).unwrap().words(),
+			&vec![(0xbcd, 1), (0x234, 1)]);
+		assert_eq!(read(Truncate::MaskHigh).unwrap().words(),
+			&vec![(0xabc, 1), (0x023, 1)]);
+		let mut mif = Mif::<u16>::new(12).unwrap();
+		mif.read(&mut &bytes[2..], 1, Lsb).unwrap();
+		assert_eq!(mif.words(), &vec![(0x234, 1)]);
+	}
+
+	#[test]
+	fn count_word_in_non_adjacent_runs() {
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		assert_eq!(mif.count_word(0xff), 0);
+		mif.push(0xff, 3).unwrap();
+		mif.push(0x00, 2).unwrap();
+		mif.push(0xff, 1).unwrap();
+		mif.push(0x01, 1).unwrap();
+		mif.push(0xff, 4).unwrap();
+		assert_eq!(mif.count_word(0xff), 8);
+		assert_eq!(mif.count_word(0x00), 2);
+		assert_eq!(mif.count_word(0x02), 0);
+	}
+
+	#[test]
+	fn fixed_address_digits() {
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		mif.push(0x11, 1).unwrap();
+		mif.push(0x22, 0x10).unwrap();
+		let digits = |digits| Style { digits, ..Style::default() };
+		assert!(mif.to_string_mif(&digits(None)).unwrap()
+			.contains("\t[01..10]  :   22;\n"));
+		assert!(mif.to_string_mif(&digits(Some(8))).unwrap().contains("\
+			\t00000000  :   11;\n\
+			\t[00000001..00000010]  :   22;\n"));
+		assert!(matches!(mif.to_string_mif(&digits(Some(1))),
+			Err(FewerDigits(1, 2))));
+		assert!(Mif::parse(&mif.to_string_mif(&digits(Some(8))).unwrap())
+			.unwrap().eq_content(&mif));
+	}
+
+	#[test]
+	fn masked_mif_display_only() {
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		mif.push(0x81, 1).unwrap();
+		mif.push(0x82, 1).unwrap();
+		mif.push(0x0c, 1).unwrap();
+		let original = mif.clone();
+		let style = Style {
+			and_mask: Some(0x0f),
+			or_mask: Some(0x30),
+			..Style::default()
+		};
+		assert!(mif.to_string_mif(&style).unwrap().ends_with("\
+			CONTENT BEGIN\n\
+			\t0  :   31;\n\
+			\t1  :   32;\n\
+			\t2  :   3C;\n\
+			END;\n"));
+		let style = Style { and_mask: Some(0x80), ..Style::default() };
+		assert!(mif.to_string_mif(&style).unwrap()
+			.contains("\t[0..1]  :   80;\n\t2  :   00;\n"));
+		assert_eq!(mif, original);
+	}
+
+	#[test]
+	fn signed_origin_straddling_zero() {
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		mif.area("a.bin".into());
+		mif.push(0x11, 0x10).unwrap();
+		mif.area("b.bin".into());
+		mif.push(0x22, 1).unwrap();
+		mif.push(0x33, 0x10).unwrap();
+		let style = Style { origin: -0x10, areas: true, ..Style::default() };
+		let runs = mif.to_string_mif(&style).unwrap();
+		assert!(runs.starts_with("-- -10: a.bin\n-- 00: b.bin\n"));
+		assert!(runs.ends_with("\
+			CONTENT BEGIN\n\
+			\t[-10..-01]  :   11;\n\
+			\t00  :   22;\n\
+			\t[01..10]  :   33;\n\
+			END;\n"));
+		let style = Style { single: true, align: true, ..style };
+		assert!(mif.to_string_mif(&style).unwrap().contains("\
+			\t-01  :   11;\n\
+			\t00   :   22;\n\
+			\t01   :   33;\n"));
+		let style = Style { origin: 0xf0, ..Style::default() };
+		assert!(mif.to_string_mif(&style).unwrap()
+			.contains("\t[0F0..0FF]  :   11;\n\t100  :   22;\n"));
+	}
+
+	#[test]
+	fn dedup_runs_idempotently() {
+		let mut mif = Mif::<u8> {
+			width: 8,
+			depth: 9,
+			words: vec![(0x11, 2), (0x11, 1), (0x22, 0), (0x11, 3), (0x22, 1),
+				(0x33, 0), (0x22, 2)],
+			areas: vec![(3, "a.bin".into())],
+			notes: Vec::new(),
+		};
+		assert!(matches!(mif.validate(), Err(NonCoalesced(2))));
+		mif.dedup_runs();
+		assert_eq!(mif.words(), &vec![(0x11, 6), (0x22, 3)]);
+		assert_eq!(mif.depth(), 9);
+		assert_eq!(mif.areas(), &vec![(3, "a.bin".into())]);
+		mif.validate().unwrap();
+		let dedup = mif.clone();
+		mif.dedup_runs();
+		assert_eq!(mif, dedup);
+	}
+
+	#[test]
+	fn inline_area_comments_in_order() {
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		mif.area("a.bin".into());
+		mif.push(0x11, 3).unwrap();
+		mif.area("b.bin".into());
+		mif.push(0x11, 2).unwrap();
+		mif.push(0x22, 1).unwrap();
+		mif.area("c.bin".into());
+		mif.push(0x33, 1).unwrap();
+		let style = Style { areas: true, inline: true, ..Style::default() };
+		assert_eq!(mif.to_string_mif(&style).unwrap(), "\
+			WIDTH=8;\n\
+			DEPTH=7;\n\
+			\n\
+			ADDRESS_RADIX=HEX;\n\
+			DATA_RADIX=HEX;\n\
+			\n\
+			CONTENT BEGIN\n\
+			\t-- 0: a.bin\n\
+			\t[0..2]  :   11;\n\
+			\t-- 3: b.bin\n\
+			\t[3..4]  :   11;\n\
+			\t5  :   22;\n\
+			\t-- 6: c.bin\n\
+			\t6  :   33;\n\
+			END;\n");
+		let style = Style { inline: false, ..style };
+		assert!(mif.to_string_mif(&style).unwrap()
+			.starts_with("-- 0: a.bin\n-- 3: b.bin\n-- 6: c.bin\n"));
+	}
+
+	#[test]
+	fn resize_growing_and_shrinking() {
+		let mut mif = Mif::<u8>::new(4).unwrap();
+		mif.resize(3, 0x1).unwrap();
+		assert_eq!(mif.words(), &vec![(0x1, 3)]);
+		mif.area("a.bin".into());
+		mif.resize(5, 0x2).unwrap();
+		mif.area("b.bin".into());
+		mif.resize(7, 0x3).unwrap();
+		let resized = mif.clone();
+		mif.resize(7, 0x4).unwrap();
+		assert_eq!(mif, resized);
+		mif.resize(4, 0x0).unwrap();
+		assert_eq!(mif.words(), &vec![(0x1, 3), (0x2, 1)]);
+		assert_eq!(mif.areas(), &vec![(3, "a.bin".into())]);
+		assert_eq!(mif.depth(), 4);
+		mif.resize(3, 0x0).unwrap();
+		assert_eq!(mif.words(), &vec![(0x1, 3)]);
+		assert!(matches!(mif.resize(2, 0x10), Err(ValueOutOfWidth(2, 4))));
+		assert!(matches!(mif.resize(4, 0x10), Err(ValueOutOfWidth(3, 4))));
+		mif.resize(0, 0x0).unwrap();
+		assert_eq!(mif.depth(), 0);
+		assert!(mif.words().is_empty());
+	}
+
+	#[test]
+	fn read_packed_nibbles_and_crumbs() {
+		let read = |width, bytes: &[u8], depth, first| {
+			let mut mif = Mif::<u16>::new(width).unwrap();
+			mif.read_packed(&mut &bytes[..], depth, first).map(|()| mif.words()
+				.iter().map(|&(word, _bulk)| word).collect::<Vec<_>>())
+		};
+		assert_eq!(read(4, &[0x21, 0x43], 4, Lsb).unwrap(), [1, 2, 3, 4]);
+		assert_eq!(read(4, &[0x21, 0x43], 4, Msb).unwrap(), [2, 1, 4, 3]);
+		assert_eq!(read(4, &[0x21, 0x43], 3, Lsb).unwrap(), [1, 2, 3]);
+		assert!(read(4, &[0x21, 0x43], 5, Lsb).is_err());
+		assert_eq!(read(2, &[0xe4], 4, Lsb).unwrap(), [0, 1, 2, 3]);
+		assert_eq!(read(2, &[0xe4], 4, Msb).unwrap(), [3, 2, 1, 0]);
+		assert_eq!(read(12, &[0xab, 0xcd, 0xef], 2, Msb).unwrap(),
+			[0xabc, 0xdef]);
+		assert_eq!(read(12, &[0xab, 0xcd, 0xef], 2, Lsb).unwrap(),
+			[0xdab, 0xefc]);
+	}
+
+	#[test]
+	fn push_slice_coalescing_repeated_words() {
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		mif.push_word(0x01).unwrap();
+		mif.push_slice(&[0x01, 0x01, 0x02, 0x03, 0x03, 0x01]).unwrap();
+		mif.push_slice(&[]).unwrap();
+		mif.push_slice(&[0x01]).unwrap();
+		assert_eq!(mif.words(), &vec![(0x01, 3), (0x02, 1), (0x03, 2),
+			(0x01, 2)]);
+		assert_eq!(mif.depth(), 8);
+		let mut narrow = Mif::<u8>::new(4).unwrap();
+		assert!(matches!(narrow.push_slice(&[0x0f, 0x10]),
+			Err(ValueOutOfWidth(1, 4))));
+	}
+
+	#[test]
+	fn read_mapped_known_scrambles() {
+		let read = |width, map: &[usize], bytes: &[u8], first| {
+			let mut mif = Mif::<u16>::new(width).unwrap();
+			mif.read_mapped(&mut &bytes[..], 1, first, map)
+				.map(|()| mif.words()[0].0)
+		};
+		let swap = (8..16).chain(0..8).collect::<Vec<_>>();
+		let reverse = (0..16).rev().collect::<Vec<_>>();
+		assert_eq!(read(16, &swap, &[0x34, 0x12], Lsb).unwrap(), 0x3412);
+		assert_eq!(read(16, &swap, &[0x34, 0x12], Msb).unwrap(), 0x1234);
+		assert_eq!(read(16, &reverse, &[0x01, 0x80], Lsb).unwrap(), 0x8001);
+		assert_eq!(read(16, &reverse, &[0x03, 0x00], Lsb).unwrap(), 0xc000);
+		assert_eq!(read(8, &[4, 5, 6, 7, 0, 1, 2, 3], &[0xa5], Lsb).unwrap(),
+			0x5a);
+		assert_eq!(read(8, &[1, 0, 3, 2, 5, 4, 7, 6], &[0x96], Lsb).unwrap(),
+			0x69);
+		assert_eq!(read(4, &[7, 6, 5, 4], &[0x10], Lsb).unwrap(), 0x8);
+		for map in [&[0, 1, 2][..], &[0, 1, 2, 2], &[0, 1, 2, 8]] {
+			assert!(matches!(read(4, map, &[0x00], Lsb),
+				Err(InvalidFieldMap(_, 4))));
+		}
+	}
+
+	#[test]
+	fn place_with_and_without_gap() {
+		let mut base = Mif::<u8>::new(8).unwrap();
+		base.push(0x11, 2).unwrap();
+		let mut piece = Mif::<u8>::new(4).unwrap();
+		piece.area("piece.bin".into());
+		piece.push(0x2, 2).unwrap();
+		let mut mif = base.clone();
+		mif.place(&piece, 4, 0xff).unwrap();
+		assert_eq!(mif.words(), &vec![(0x11, 2), (0xff, 2), (0x02, 2)]);
+		assert_eq!(mif.depth(), 6);
+		assert!(mif.areas().is_empty());
+		let mut mif = base.clone();
+		mif.place(&piece, 2, 0xff).unwrap();
+		assert_eq!(mif.words(), &vec![(0x11, 2), (0x02, 2)]);
+		let mut mif = base.clone();
+		assert!(matches!(mif.place(&piece, 1, 0xff), Err(OutOfOrder(1))));
+		let wide = Mif::<u8>::new(8).unwrap();
+		let mut narrow = Mif::<u8>::new(4).unwrap();
+		assert!(matches!(narrow.place(&wide, 0, 0x0), Err(WiderWidth(8, 4))));
+		assert_eq!(mif, base);
+	}
+
+	#[test]
+	fn trailing_checksums_of_known_vectors() {
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		mif.push_slice(b"123456789").unwrap();
+		mif.push(0x00, 2).unwrap();
+		assert_eq!(mif.apply_trailing_checksum(Footer::Crc16, 2).unwrap(),
+			0x29b1);
+		assert_eq!(&mif.words()[9..], &[(0xb1, 1), (0x29, 1)]);
+		assert!(mif.has_trailing_checksum(Footer::Crc16, 2).unwrap());
+		assert!(!mif.has_trailing_checksum(Footer::SumToZero, 2).unwrap());
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		mif.push_slice(&[1, 2, 3, 0]).unwrap();
+		assert_eq!(mif.apply_trailing_checksum(Footer::SumToZero, 1).unwrap(),
+			0xfa);
+		assert!(mif.has_trailing_checksum(Footer::SumToZero, 1).unwrap());
+		assert_eq!(mif.apply_trailing_checksum(Footer::SumComplement, 1)
+			.unwrap(), 0xf9);
+		assert!(!mif.has_trailing_checksum(Footer::SumToZero, 1).unwrap());
+		assert!(matches!(mif.trailing_checksum(Footer::SumToZero, 0),
+			Err(InvalidFooter(0, 8))));
+		assert!(matches!(mif.trailing_checksum(Footer::Crc16, 1),
+			Err(InvalidFooter(1, 8))));
+		assert!(matches!(mif.trailing_checksum(Footer::SumToZero, 17),
+			Err(InvalidFooter(17, 8))));
+		for words in [usize::MAX, usize::MAX / 8 + 1] {
+			assert!(matches!(mif.trailing_checksum(Footer::Crc16, words),
+				Err(InvalidFooter(_, 8))));
+		}
+		assert!(matches!(mif.trailing_checksum(Footer::SumToZero, 5),
+			Err(DepthExceeded(5, 4))));
+	}
+
+	#[test]
+	fn word_and_byte_at_of_odd_width() {
+		let mut mif = Mif::<u32>::new(20).unwrap();
+		mif.push(0xabcde, 2).unwrap();
+		mif.push(0x12345, 1).unwrap();
+		assert_eq!(mif.word_at(1), Some(0xabcde));
+		assert_eq!(mif.word_at(2), Some(0x12345));
+		assert_eq!(mif.word_at(3), None);
+		let bytes = |addr, first| (0..4)
+			.map(|index| mif.byte_at(addr, index, first)).collect::<Vec<_>>();
+		assert_eq!(bytes(2, Lsb), [Some(0x45), Some(0x23), Some(0x01), None]);
+		assert_eq!(bytes(2, Msb), [Some(0x01), Some(0x23), Some(0x45), None]);
+		assert_eq!(bytes(0, Msb), [Some(0x0a), Some(0xbc), Some(0xde), None]);
+		assert_eq!(bytes(3, Lsb), [None; 4]);
+		let mut written = Vec::new();
+		mif.write_bytes(&mut written, Msb).unwrap();
+		assert_eq!(written[6..], [0x01, 0x23, 0x45]);
+	}
+
+	#[test]
+	fn runs_in_range_clipped() {
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		mif.push(0x11, 10).unwrap();
+		mif.push(0x22, 5).unwrap();
+		mif.push(0x33, 5).unwrap();
+		let runs = |range| mif.runs_in_range(range).collect::<Vec<_>>();
+		assert_eq!(runs(2..7), [(2, 5, 0x11)]);
+		assert_eq!(runs(8..17), [(8, 2, 0x11), (10, 5, 0x22), (15, 2, 0x33)]);
+		assert_eq!(runs(10..15), [(10, 5, 0x22)]);
+		assert_eq!(runs(0..30), [(0, 10, 0x11), (10, 5, 0x22), (15, 5, 0x33)]);
+		assert_eq!(runs(19..30), [(19, 1, 0x33)]);
+		assert_eq!(runs(5..5), []);
+		assert_eq!(runs(20..30), []);
+	}
+
+	#[test]
+	fn stepped_addresses() {
+		let mut mif = Mif::<u32>::new(32).unwrap();
+		mif.area("a.bin".into());
+		mif.push(0x1111_1111, 4).unwrap();
+		mif.push(0x2222_2222, 1).unwrap();
+		mif.note("end".into());
+		let style = Style {
+			areas: true,
+			sources: true,
+			addr_step: Some(mif.align()),
+			..Style::default()
+		};
+		let stepped = mif.to_string_mif(&style).unwrap();
+		assert!(stepped.starts_with("-- 00: a.bin\n-- 14: end\n"));
+		assert!(stepped.ends_with("\
+			CONTENT BEGIN\n\
+			\t[00..0C]  :   11111111; -- from a.bin\n\
+			\t10  :   22222222; -- from a.bin\n\
+			END;\n"));
+		let style = Style { addr_step: Some(0), ..style };
+		assert!(mif.to_string_mif(&style).unwrap().contains("\t4  :   "));
+		for step in [usize::MAX / 4, usize::MAX] {
+			let style = Style { addr_step: Some(step), ..style };
+			assert!(matches!(mif.to_string_mif(&style),
+				Err(AddressOutOfRange(5))));
+		}
+	}
+
+	#[test]
+	fn compression_ratio_of_unique_and_same_words() {
+		let mut unique = Mif::<u16>::new(16).unwrap();
+		assert_eq!((unique.run_count(), unique.compression_ratio()), (0, 0.0));
+		unique.push_slice(&(0..256).collect::<Vec<_>>()).unwrap();
+		assert_eq!((unique.run_count(), unique.compression_ratio()),
+			(256, 1.0));
+		let mut same = Mif::<u16>::new(16).unwrap();
+		same.push(0xffff, 256).unwrap();
+		assert_eq!((same.run_count(), same.compression_ratio()),
+			(1, 1.0 / 256.0));
+	}
+
+	#[test]
+	fn header_first_golden() {
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		mif.area("a.bin".into());
+		mif.push(0x01, 2).unwrap();
+		mif.area("b.bin".into());
+		mif.push(0x02, 1).unwrap();
+		let style = Style {
+			areas: true,
+			header_first: true,
+			..Style::default()
+		};
+		assert_eq!(mif.to_string_mif(&style).unwrap(), "\
+			WIDTH=8;\n\
+			DEPTH=3;\n\
+			\n\
+			ADDRESS_RADIX=HEX;\n\
+			DATA_RADIX=HEX;\n\
+			\n\
+			-- 0: a.bin\n\
+			-- 2: b.bin\n\
+			\n\
+			CONTENT BEGIN\n\
+			\t[0..1]  :   01;\n\
+			\t2  :   02;\n\
+			END;\n");
+	}
+
+	#[test]
+	fn dominant_word_of_majority_and_even_split() {
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		assert_eq!(mif.dominant_word(), None);
+		assert_eq!(mif.fill_ratio_of(0x00), 0.0);
+		mif.push(0xff, 3).unwrap();
+		mif.push(0x00, 1).unwrap();
+		mif.push(0xff, 4).unwrap();
+		assert_eq!(mif.fill_ratio_of(0xff), 0.875);
+		assert_eq!(mif.fill_ratio_of(0x00), 0.125);
+		assert_eq!(mif.fill_ratio_of(0x01), 0.0);
+		assert_eq!(mif.dominant_word(), Some((0xff, 0.875)));
+		mif.push(0x11, 7).unwrap();
+		mif.push(0x00, 1).unwrap();
+		assert_eq!(mif.fill_ratio_of(0xff), 0.4375);
+		assert_eq!(mif.dominant_word(), Some((0x11, 0.4375)));
+	}
+
+	#[test]
+	fn diff_runs_of_changed_word_and_replaced_run() {
+		let mut a = Mif::<u8>::new(8).unwrap();
+		a.push(0x11, 100).unwrap();
+		a.push(0x22, 10).unwrap();
+		assert_eq!(a.diff_runs(&a.clone()), []);
+		let mut b = Mif::<u8>::new(8).unwrap();
+		b.push(0x11, 41).unwrap();
+		b.push(0x33, 1).unwrap();
+		b.push(0x11, 58).unwrap();
+		b.push(0x44, 10).unwrap();
+		let diff = |start, end, a_word, b_word|
+			RunDiff { start, end, a_word, b_word };
+		assert_eq!(a.diff_runs(&b), [
+			diff(41, 42, Some(0x11), Some(0x33)),
+			diff(100, 110, Some(0x22), Some(0x44)),
+		]);
+		b.push(0x55, 2).unwrap();
+		assert_eq!(b.diff_runs(&a), [
+			diff(41, 42, Some(0x33), Some(0x11)),
+			diff(100, 110, Some(0x44), Some(0x22)),
+			diff(110, 112, Some(0x55), None),
+		]);
+	}
+
+	#[test]
+	fn align_columns_of_single_and_range_lines() {
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		mif.push(0x01, 1).unwrap();
+		mif.push(0x02, 16).unwrap();
+		mif.push(0x03, 1).unwrap();
+		let header = "\
+			WIDTH=8;\n\
+			DEPTH=18;\n\
+			\n\
+			ADDRESS_RADIX=HEX;\n\
+			DATA_RADIX=HEX;\n\
+			\n\
+			CONTENT BEGIN\n";
+		let style = Style { align: true, ..Style::default() };
+		assert_eq!(mif.to_string_mif(&style).unwrap(), header.to_owned() + "\
+			\t00        :   01;\n\
+			\t[01..10]  :   02;\n\
+			\t11        :   03;\n\
+			END;\n");
+		let style = Style { align: true, origin: -2, ..Style::default() };
+		assert_eq!(mif.to_string_mif(&style).unwrap(), header.to_owned() + "\
+			\t-2        :   01;\n\
+			\t[-1..E]   :   02;\n\
+			\tF         :   03;\n\
+			END;\n");
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		mif.push_slice(&[0x01, 0x02, 0x02]).unwrap();
+		let style = Style { align: true, single: true, ..Style::default() };
+		assert_eq!(mif.to_string_mif(&style).unwrap(), "\
+			WIDTH=8;\n\
+			DEPTH=3;\n\
+			\n\
+			ADDRESS_RADIX=HEX;\n\
+			DATA_RADIX=HEX;\n\
+			\n\
+			CONTENT BEGIN\n\
+			\t0  :   01;\n\
+			\t1  :   02;\n\
+			\t2  :   02;\n\
+			END;\n");
+	}
+
+	#[test]
+	fn extend_from_reader_to_clean_and_partial_end() {
+		let mut mif = Mif::<u16>::new(16).unwrap();
+		mif.push_word(0x0001).unwrap();
+		let mut bytes = (&[0x01, 0x00, 0x02][..])
+			.chain(&[0x00, 0x02, 0x00][..]);
+		assert_eq!(mif.extend_from_reader(&mut bytes, Lsb).unwrap(), 3);
+		assert_eq!(mif.words(), &[(0x0001, 2), (0x0002, 2)]);
+		assert_eq!(mif.extend_from_reader(&mut &[][..], Msb).unwrap(), 0);
+		assert_eq!(mif.depth(), 4);
+		let mut bytes = &[0x12, 0x34, 0x56][..];
+		assert!(matches!(mif.extend_from_reader(&mut bytes, First::Msb),
+			Err(MissingWords(1))));
+		assert_eq!(mif.words().last(), Some(&(0x1234, 1)));
+		let mut mif = Mif::<u16>::new(12).unwrap();
+		let mut bytes = &[0xff, 0x0f, 0x00, 0x10][..];
+		assert!(matches!(mif.extend_from_reader(&mut bytes, First::Lsb),
+			Err(ValueOutOfWidth(1, 12))));
+		assert_eq!(mif.words(), &[(0x0fff, 1)]);
+	}
+
+	#[test]
+	fn read_grouped_mixed_endian() {
+		let read = |bytes: &[u8], first, group, groups| {
+			let mut mif = Mif::<u32>::new(32).unwrap();
+			mif.read_grouped(&mut &bytes[..], 1, first, group, groups)
+				.map(|()| mif.words()[0].0)
+		};
+		let swapped = [0x34, 0x12, 0x78, 0x56];
+		assert_eq!(read(&swapped, Lsb, 2, Msb).unwrap(), 0x1234_5678);
+		let pdp = [0x56, 0x78, 0x12, 0x34];
+		assert_eq!(read(&pdp, Msb, 2, Lsb).unwrap(), 0x1234_5678);
+		let bytes = [0x12, 0x34, 0x56, 0x78];
+		assert_eq!(read(&bytes, Msb, 4, Lsb).unwrap(), 0x1234_5678);
+		assert_eq!(read(&bytes, Lsb, 1, Msb).unwrap(), 0x1234_5678);
+		assert_eq!(read(&bytes, Lsb, 2, Lsb).unwrap(), 0x7856_3412);
+		assert!(matches!(read(&bytes, Lsb, 0, Msb), Err(InvalidGroup(0, 4))));
+		assert!(matches!(read(&bytes, Lsb, 3, Msb), Err(InvalidGroup(3, 4))));
+		assert!(read(&bytes[..3], Lsb, 2, Msb).is_err());
+	}
+
+	#[test]
+	fn set_width_widening_and_narrowing() {
+		let mut mif = Mif::<u16>::new(12).unwrap();
+		mif.push(0x00f, 2).unwrap();
+		mif.push(0x0ff, 3).unwrap();
+		mif.set_width(12).unwrap();
+		mif.set_width(16).unwrap();
+		assert_eq!((mif.width(), mif.align()), (16, 2));
+		mif.set_width(8).unwrap();
+		assert_eq!((mif.width(), mif.align()), (8, 1));
+		assert!(matches!(mif.set_width(4), Err(ValueOutOfWidth(2, 4))));
+		assert!(matches!(mif.set_width(0), Err(WidthOutOfRange(0, 16, _))));
+		assert!(matches!(mif.set_width(17), Err(WidthOutOfRange(17, 16, _))));
+		assert_eq!(mif.width(), 8);
+		assert_eq!(mif.words(), &[(0x00f, 2), (0x0ff, 3)]);
+	}
+
+	#[test]
+	fn generic_dialect_golden() {
+		let mut mif = Mif::<u16>::new(12).unwrap();
+		mif.area("a.bin".into());
+		mif.push(0xabc, 2).unwrap();
+		mif.push(0x001, 1).unwrap();
+		let style = Style {
+			areas: true,
+			dialect: Dialect::Generic,
+			..Style::default()
+		};
+		assert_eq!(mif.to_string_mif(&style).unwrap(), "\
+			WIDTH=12;\n\
+			DEPTH=3;\n\
+			\n\
+			ADDRESS_RADIX=HEX;\n\
+			DATA_RADIX=HEX;\n\
+			\n\
+			-- 0: a.bin\n\
+			\n\
+			CONTENT BEGIN\n\
+			0 : ABC;\n\
+			1 : ABC;\n\
+			2 : 001;\n\
+			END;\n");
+	}
+
+	#[test]
+	fn gowin_dialect_golden() {
+		let mut mif = Mif::<u16>::new(12).unwrap();
+		mif.area("a.bin".into());
+		mif.push(0xabc, 2).unwrap();
+		mif.push(0x001, 1).unwrap();
+		let style = Style {
+			case: HexCase::Lower,
+			dialect: Dialect::Gowin,
+			and_mask: Some(0xff0),
+			..Style::default()
+		};
+		assert_eq!(mif.to_string_mif(&style).unwrap(), "\
+			#File_format=Hex\n\
+			#Memory_format=Hex\n\
+			#Address_depth=3\n\
+			#Data_width=12\n\
+			ab0\n\
+			ab0\n\
+			000\n");
+		for style in [
+			Style { areas: true, ..style },
+			Style { origin: 1, ..style },
+			Style { digits: Some(4), ..style },
+			Style { align: true, ..style },
+			Style { version: Some("0.3.0"), ..style },
+		] {
+			assert!(matches!(mif.to_string_mif(&style),
+				Err(UnsupportedStyle("Gowin"))));
+		}
+	}
+
+	#[test]
+	fn convert_narrowing_and_out_of_type() {
+		let mut wide = Mif::<u128>::new(64).unwrap();
+		wide.area("a.bin".into());
+		wide.push(0xffff_ffff_ffff_ffff, 2).unwrap();
+		wide.push(0x1, 1).unwrap();
+		wide.note("end".into());
+		let narrow = Mif::<u64>::try_from(wide.clone()).unwrap();
+		assert_eq!((narrow.width(), narrow.depth()), (64, 3));
+		assert_eq!(narrow.words(), &[(0xffff_ffff_ffff_ffff, 2), (0x1, 1)]);
+		assert_eq!(narrow.areas(), wide.areas());
+		assert_eq!(narrow.notes(), wide.notes());
+		assert!(matches!(Mif::<u32>::try_from(wide),
+			Err(ValueOutOfType(0, "u32"))));
+		let mut wide = Mif::<u128>::new(65).unwrap();
+		wide.push(0x1, 2).unwrap();
+		wide.push(0x1_0000_0000_0000_0000, 1).unwrap();
+		assert!(matches!(wide.clone().convert::<u64>(),
+			Err(ValueOutOfType(2, "u64"))));
+		wide.resize(2, 0).unwrap();
+		assert!(matches!(wide.convert::<u64>(),
+			Err(WidthOutOfRange(65, 64, "u64"))));
+	}
+
+	#[test]
+	fn address_pads_at_powers_of_sixteen() {
+		for &(depth, origin, step, range) in &[
+			(16, 0, 1, "[0..F]"),
+			(17, 0, 1, "[00..10]"),
+			(256, 0, 1, "[00..FF]"),
+			(257, 0, 1, "[000..100]"),
+			(4096, 0, 1, "[000..FFF]"),
+			(4097, 0, 1, "[0000..1000]"),
+			(16, 0x10, 1, "[10..1F]"),
+			(256, 0x10, 1, "[010..10F]"),
+			(4096, 0x10, 1, "[0010..100F]"),
+			(16, 0, 4, "[00..3C]"),
+			(17, 0, 4, "[00..40]"),
+			(256, 0, 4, "[000..3FC]"),
+			(257, 0, 4, "[000..400]"),
+			(4096, 0, 4, "[0000..3FFC]"),
+			(4097, 0, 4, "[0000..4000]"),
+			(16, -0x10, 1, "[-10..-01]"),
+			(17, -0x10, 1, "[-10..00]"),
+			(257, -0x100, 1, "[-100..000]"),
+			(4097, -0x100, 1, "[-100..F00]"),
+		] {
+			let mut mif = Mif::<u8>::new(8).unwrap();
+			mif.push(0x00, depth).unwrap();
+			let addr_step = Some(step);
+			let style = Style { origin, addr_step, ..Style::default() };
+			let line = format!("\t{}  :   00;\n", range);
+			assert!(mif.to_string_mif(&style).unwrap().contains(&line),
+				"{} {} {}", depth, origin, step);
+		}
+	}
+
+	#[test]
+	fn lowercase_keywords_of_dialects() {
+		let mut mif = Mif::<u8>::new(8).unwrap();
+		mif.push(0xab, 2).unwrap();
+		let style = Style { keywords: KeywordCase::Lower, ..Style::default() };
+		let text = mif.to_string_mif(&style).unwrap();
+		assert_eq!(text, "\
+			width=8;\n\
+			depth=2;\n\
+			\n\
+			address_radix=hex;\n\
+			data_radix=hex;\n\
+			\n\
+			content begin\n\
+			\t[0..1]  :   AB;\n\
+			end;\n");
+		assert_eq!(Mif::<u8>::parse(&text).unwrap(), mif);
+		let style = Style { dialect: Dialect::Generic, ..style };
+		let text = mif.to_string_mif(&style).unwrap();
+		for keyword in ["width=8;\n", "depth=2;\n", "address_radix=hex;\n",
+			"data_radix=hex;\n", "content begin\n", "end;\n"] {
+			assert!(text.contains(keyword), "{}", keyword);
+			assert!(!text.contains(&keyword.to_ascii_uppercase()));
+		}
+		assert!(text.contains("0 : AB;\n1 : AB;\n"));
+		let style = Style { dialect: Dialect::Gowin, ..style };
+		assert!(matches!(mif.to_string_mif(&style),
+			Err(UnsupportedStyle("Gowin"))));
+	}
+
+	#[test]
+	fn append_bytes_all_or_nothing() {
+		let mut mif = Mif::<u8>::new(4).unwrap();
+		mif.append_bytes(&[1, 2, 2], Lsb).unwrap();
+		mif.append_bytes(&[2, 3], Lsb).unwrap();
+		assert_eq!(mif.words, [(1, 1), (2, 3), (3, 1)]);
+		assert!(matches!(mif.append_bytes(&[3, 4, 0x10], Lsb),
+			Err(ValueOutOfWidth(7, 4))));
+		assert_eq!(mif.words, [(1, 1), (2, 3), (3, 1)]);
+		assert_eq!(mif.depth(), 5);
+		let mut mif = Mif::<u16>::new(16).unwrap();
+		assert!(matches!(mif.append_bytes(&[1, 2, 3], Msb),
+			Err(PartialWord(1, 2))));
+		assert_eq!(mif.depth(), 0);
+		mif.append_bytes(&[1, 2], Msb).unwrap();
+		assert_eq!(mif.words, [(0x0102, 1)]);
+	}
+}
@@ -0,0 +1,667 @@
+//! MIF parser and its tokenizer for line-by-line processing as well as Intel
+//! HEX and S-record parsers.
+
+use std::{convert::TryFrom, fmt::UpperHex, collections::BTreeMap};
+use num_traits::{
+	sign::Unsigned, int::PrimInt, cast::FromPrimitive,
+	ops::{checked::CheckedShl, wrapping::WrappingSub},
+};
+use crate::{Mif, First, Result, Error::{
+	Syntax, DepthExceeded, ValueOutOfWidth, OutOfOrder, DuplicateAddress,
+	NonCoalesced, Checksum,
+}};
+
+impl<T> Mif<T>
+where
+	T: UpperHex + Unsigned + PrimInt + FromPrimitive + CheckedShl + WrappingSub,
+{
+	/// Parses MIF `text`, see `tokens()`.
+	///
+	/// Addresses and words are interpreted in the radix declared by the
+	/// `ADDRESS_RADIX` and `DATA_RADIX` headers, defaulting to `HEX`. Supported
+	/// radixes are `BIN`, `OCT`, `DEC`, `UNS`, and `HEX` where only `DEC` words
+	/// may be negative and are stored in two's complement of `width()` bits.
+	///
+	/// Unspecified addresses are filled with zero words up to `DEPTH`. Several
+	/// words at one address as in `0 : 1 2;` occupy subsequent addresses
+	/// whereas several words of a range as in `[0..3] : 1 2;` are repeated over
+	/// the range. Comments are ignored.
+	///
+	/// Non-canonical MIFs are accepted and normalized, that is addresses may
+	/// be out of order, later words override earlier words at duplicate
+	/// addresses, and runs are coalesced, see `parse_strict()` to reject them.
+	///
+	/// Words are numbers independent of byte order which is only chosen when
+	/// writing them as bytes, see `write_bytes()`.
+	pub fn parse(text: &str) -> Result<Self> {
+		Self::parse_with(text, false)
+	}
+	/// Parses canonical MIF `text` as written by `write()`, see `parse()`.
+	///
+	/// Errors on addresses out of order, duplicate addresses, and runs that
+	/// could have been coalesced with their preceding run.
+	pub fn parse_strict(text: &str) -> Result<Self> {
+		Self::parse_with(text, true)
+	}
+	/// Parses MIF `text` rejecting non-canonical MIFs if `strict`.
+	///
+	/// Skips comments while tokenizing, see `Tokens::without_comments()`.
+	fn parse_with(text: &str, strict: bool) -> Result<Self> {
+		let mut tokens = tokens(text).without_comments();
+		let (mut width, mut depth) = (None, None);
+		let (mut addr_radix, mut data_radix) = (Radix::Hex, Radix::Hex);
+		let mut mif: Option<(Self, Runs<T>)> = None;
+		let mut end = false;
+		while let Some(token) = tokens.next() {
+			let line = tokens.line();
+			let addr = |addr| addr_radix.value(addr, None)
+				.and_then(|addr| usize::try_from(addr).ok())
+				.ok_or(Syntax(line));
+			match (token?, mif.as_mut()) {
+				(_, _) if end => Err(Syntax(line))?,
+				(Token::Header { key, val }, None) => {
+					let number = || val.parse().map_err(|_| Syntax(line));
+					match key.to_ascii_uppercase().as_str() {
+						"WIDTH" => width = Some(number()?),
+						"DEPTH" => depth = Some(number()?),
+						"ADDRESS_RADIX" => addr_radix = Radix::from(val, line)?,
+						"DATA_RADIX" => data_radix = Radix::from(val, line)?,
+						_ => Err(Syntax(line))?,
+					}
+				},
+				(Token::Begin, None) => {
+					let (width, depth) = width.zip(depth).ok_or(Syntax(line))?;
+					mif = Some((Self::new(width)?, Runs::new(depth, strict)));
+				},
+				(Token::Single { addr: first, word }, Some((mif, runs))) => {
+					let first = addr(first)?;
+					for (index, word) in word.split_whitespace().enumerate() {
+						let addr = first.checked_add(index)
+							.ok_or(Syntax(line))?;
+						let word = mif.word(data_radix, word, addr, line)?;
+						runs.paint(addr, 1, word)?;
+					}
+				},
+				(Token::Range { first, last, word }, Some((mif, runs))) => {
+					let (first, last) = (addr(first)?, addr(last)?);
+					let bulk = last.checked_sub(first)
+						.and_then(|bulk| bulk.checked_add(1))
+						.ok_or(Syntax(line))?;
+					let words = word.split_whitespace().collect::<Vec<_>>();
+					if let &[word] = words.as_slice() {
+						runs.paint(first, bulk,
+							mif.word(data_radix, word, first, line)?)?;
+					} else {
+						let words = (first..=last).zip(words.iter().cycle());
+						for (addr, word) in words {
+							runs.paint(addr, 1,
+								mif.word(data_radix, word, addr, line)?)?;
+						}
+					}
+				},
+				(Token::End, Some(_)) => end = true,
+				_ => Err(Syntax(line))?,
+			}
+		}
+		match mif {
+			Some((mut mif, runs)) if end => {
+				for (first, (last, word)) in runs.map {
+					mif.push(T::zero(), first - mif.depth())?;
+					mif.push(word, last - first)?;
+				}
+				mif.push(T::zero(), runs.depth - mif.depth())?;
+				Ok(mif)
+			},
+			_ => Err(Syntax(tokens.line())),
+		}
+	}
+	/// Word of `radix` at `addr` and `line`.
+	fn word(&self, radix: Radix, word: &str, addr: usize, line: usize)
+	-> Result<T> {
+		let word = radix.value(word, Some(self.width())).ok_or(Syntax(line))?;
+		T::from_u128(word).ok_or(ValueOutOfWidth(addr, self.width()))
+	}
+}
+
+/// Disjoint runs of words by first address and last address plus one.
+struct Runs<T> {
+	map: BTreeMap<usize, (usize, T)>,
+	last: Option<(usize, usize, T)>,
+	depth: usize,
+	strict: bool,
+}
+
+impl<T: PrimInt> Runs<T> {
+	/// Creates new runs up to `depth` rejecting non-canonical runs if `strict`.
+	fn new(depth: usize, strict: bool) -> Self {
+		Runs { map: BTreeMap::new(), last: None, depth, strict }
+	}
+	/// Paints `bulk` of `word` at address `first` over previous runs.
+	fn paint(&mut self, first: usize, bulk: usize, word: T) -> Result<()> {
+		let last = first.checked_add(bulk).filter(|&last| last <= self.depth)
+			.ok_or(DepthExceeded(first.saturating_add(bulk), self.depth))?;
+		if let (true, Some((prev_first, prev_last, prev_word)))
+		= (self.strict, self.last) {
+			if first < prev_first {
+				Err(OutOfOrder(first))?;
+			}
+			if first < prev_last {
+				Err(DuplicateAddress(first))?;
+			}
+			if first == prev_last && word == prev_word {
+				Err(NonCoalesced(first))?;
+			}
+		}
+		self.last = Some((first, last, word));
+		let overlaps = self.map.range(..last).rev()
+			.take_while(|&(_, &(end, _))| end > first)
+			.map(|(&start, &(end, word))| (start, end, word))
+			.collect::<Vec<_>>();
+		for (start, end, over) in overlaps {
+			self.map.remove(&start);
+			if start < first {
+				self.map.insert(start, (first, over));
+			}
+			if end > last {
+				self.map.insert(last, (end, over));
+			}
+		}
+		self.map.insert(first, (last, word));
+		Ok(())
+	}
+}
+
+/// Radix of addresses or words.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+enum Radix {
+	Bin,
+	Oct,
+	Dec,
+	Uns,
+	Hex,
+}
+
+impl Radix {
+	/// Radix of `val` header at `line`.
+	fn from(val: &str, line: usize) -> Result<Self> {
+		Ok(match val.to_ascii_uppercase().as_str() {
+			"BIN" => Radix::Bin,
+			"OCT" => Radix::Oct,
+			"DEC" => Radix::Dec,
+			"UNS" => Radix::Uns,
+			"HEX" => Radix::Hex,
+			_ => Err(Syntax(line))?,
+		})
+	}
+	/// Value of `text`, negative values of `DEC` in two's complement of
+	/// `width` bits if given.
+	fn value(self, text: &str, width: Option<usize>) -> Option<u128> {
+		let radix = match self {
+			Radix::Bin => 2,
+			Radix::Oct => 8,
+			Radix::Dec | Radix::Uns => 10,
+			Radix::Hex => 16,
+		};
+		match (self, width) {
+			(Radix::Dec, Some(width)) if text.starts_with('-') => {
+				let value = text.parse::<i128>().ok()?;
+				if value < -1 << (width - 1) {
+					return None;
+				}
+				Some(value as u128 & (u128::MAX >> (128 - width)))
+			},
+			_ => u128::from_str_radix(text, radix).ok(),
+		}
+	}
+}
+
+/// MIF token borrowing from the tokenized text.
+///
+/// Addresses and words are given as written, that is in the radix declared by
+/// the `ADDRESS_RADIX` and `DATA_RADIX` headers. A `word` may comprise several
+/// whitespace-separated words.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Token<'a> {
+	/// Header as in `WIDTH=16;` with `key` and `val` trimmed.
+	Header {
+		/// Key as in `WIDTH`.
+		key: &'a str,
+		/// Value as in `16`.
+		val: &'a str,
+	},
+	/// Single word at address as in `0  :   ABCD;`.
+	Single {
+		/// Address as in `0`.
+		addr: &'a str,
+		/// Word as in `ABCD`.
+		word: &'a str,
+	},
+	/// Range of addresses of the same word as in `[0..F]  :   ABCD;`.
+	Range {
+		/// First address as in `0`.
+		first: &'a str,
+		/// Last address as in `F`.
+		last: &'a str,
+		/// Word as in `ABCD`.
+		word: &'a str,
+	},
+	/// Comment as in `-- text` or `% text %` without delimiters.
+	Comment(&'a str),
+	/// Begin of content as in `CONTENT BEGIN`.
+	Begin,
+	/// End of content as in `END;`.
+	End,
+}
+
+/// LSB/MSB first commented as in `-- FIRST=lsb` before `CONTENT BEGIN` of MIF
+/// `text`, see `Style::first`.
+pub fn first(text: &str) -> Result<Option<First>> {
+	for token in tokens(text) {
+		match token? {
+			Token::Comment(comment) => if let Some(first) = comment
+				.strip_prefix("FIRST=") {
+				return first.trim().parse().map(Some);
+			},
+			Token::Begin => break,
+			_ => {},
+		}
+	}
+	Ok(None)
+}
+
+/// Tokenizes MIF `text`, see `Tokens`.
+pub fn tokens(text: &str) -> Tokens<'_> {
+	Tokens { rest: text, line: 1, next: 1, comments: true }
+}
+
+/// Iterator of MIF tokens, see `tokens()`.
+///
+/// Yields `Syntax` error once at the first invalid statement and stops.
+#[derive(Debug, Clone)]
+pub struct Tokens<'a> {
+	rest: &'a str,
+	line: usize,
+	next: usize,
+	comments: bool,
+}
+
+impl<'a> Tokens<'a> {
+	/// Skips comments instead of yielding them.
+	///
+	/// Faster for MIFs of many comments as only content is materialized.
+	/// Invalid comments as in `%` without closing `%` still fail.
+	pub fn without_comments(mut self) -> Self {
+		self.comments = false;
+		self
+	}
+	/// Line number of last token starting at one.
+	pub fn line(&self) -> usize {
+		self.line
+	}
+	/// Consumes `count` bytes of rest and returns them.
+	fn take(&mut self, count: usize) -> &'a str {
+		let (taken, rest) = self.rest.split_at(count);
+		self.next += taken.bytes().filter(|&byte| byte == b'\n').count();
+		self.rest = rest;
+		taken
+	}
+	/// Fails at current line and stops.
+	fn fail(&mut self) -> Option<Result<Token<'a>>> {
+		self.rest = "";
+		Some(Err(Syntax(self.line)))
+	}
+}
+
+impl<'a> Iterator for Tokens<'a> {
+	type Item = Result<Token<'a>>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let blank = self.rest.len() - self.rest.trim_start().len();
+			self.take(blank);
+			self.line = self.next;
+			if self.rest.is_empty() {
+				return None;
+			}
+			if self.rest.starts_with("--") {
+				let end = self.rest.find('\n').unwrap_or(self.rest.len());
+				if self.comments {
+					return Some(Ok(Token::Comment(self.take(end)[2..].trim())));
+				}
+				// Line comment without line feed to count.
+				self.rest = &self.rest[end..];
+			} else if self.rest.starts_with('%') {
+				match self.rest[1..].find('%') {
+					Some(end) if self.comments =>
+						return Some(Ok(Token::Comment(self.take(end + 2)
+							[1..end + 1].trim()))),
+					Some(end) => { self.take(end + 2); },
+					None => return self.fail(),
+				}
+			} else {
+				break;
+			}
+		}
+		if let Some(content) = self.rest.get(..7)
+			.filter(|content| content.eq_ignore_ascii_case("CONTENT")) {
+			let rest = &self.rest[7..];
+			let blank = rest.len() - rest.trim_start().len();
+			let begin = content.len() + blank;
+			if blank > 0 && self.rest.get(begin..begin + 5)
+				.filter(|begin| begin.eq_ignore_ascii_case("BEGIN")).is_some() {
+				self.take(begin + 5);
+				return Some(Ok(Token::Begin));
+			}
+		}
+		let end = match self.rest.find(';') {
+			Some(end) => end,
+			None => return self.fail(),
+		};
+		let statement = self.take(end + 1)[..end].trim();
+		if statement.eq_ignore_ascii_case("END") {
+			return Some(Ok(Token::End));
+		}
+		if let Some(colon) = statement.find(':') {
+			let addr = statement[..colon].trim();
+			let word = statement[colon + 1..].trim();
+			if addr.is_empty() || word.is_empty() {
+				return self.fail();
+			}
+			if addr.starts_with('[') && addr.ends_with(']') {
+				let range = &addr[1..addr.len() - 1];
+				return match range.find("..") {
+					Some(dots) if !range[..dots].trim().is_empty()
+					&& !range[dots + 2..].trim().is_empty() =>
+						Some(Ok(Token::Range {
+							first: range[..dots].trim(),
+							last: range[dots + 2..].trim(),
+							word,
+						})),
+					_ => self.fail(),
+				};
+			}
+			return Some(Ok(Token::Single { addr, word }));
+		}
+		if let Some(equal) = statement.find('=') {
+			let key = statement[..equal].trim();
+			let val = statement[equal + 1..].trim();
+			if key.is_empty() || val.is_empty() {
+				return self.fail();
+			}
+			return Some(Ok(Token::Header { key, val }));
+		}
+		self.fail()
+	}
+}
+
+/// Parses Intel HEX `text` as bytes, see `Mif::write_ihex()`.
+///
+/// Supports data, end of file, extended segment address, and extended linear
+/// address records whereas start address records are ignored. Bytes range from
+/// the lowest to the highest address given by data records where addresses
+/// without data are filled with `0xFF` as of erased flash memory and later
+/// records override earlier ones. Errors on invalid checksums.
+pub fn ihex(text: &str) -> Result<Vec<u8>> {
+	let mut base = 0;
+	let mut chunks = Vec::new();
+	for record in records(text, ':') {
+		let (line, record) = record?;
+		let bytes = hex(record, line)?;
+		if bytes.len() < 5 || bytes[0] as usize != bytes.len() - 5 {
+			Err(Syntax(line))?;
+		}
+		if bytes.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte)) != 0 {
+			Err(Checksum(line))?;
+		}
+		let addr = be(&bytes[1..3]);
+		let data = &bytes[4..bytes.len() - 1];
+		match (bytes[3], data.len()) {
+			(0, _) => chunks.push((base + addr, data.to_vec())),
+			(1, 0) => break,
+			(2, 2) => base = be(data) << 4,
+			(4, 2) => base = be(data) << 16,
+			(3, 4) | (5, 4) => {},
+			_ => Err(Syntax(line))?,
+		}
+	}
+	Ok(image(chunks))
+}
+
+/// Parses Motorola S-record `text` as bytes, see `Mif::write_srec()`.
+///
+/// Supports `S1`, `S2`, and `S3` data records whereas header, count, and
+/// termination records are ignored. Bytes are arranged as by `ihex()`. Errors
+/// on invalid checksums.
+pub fn srec(text: &str) -> Result<Vec<u8>> {
+	let mut chunks = Vec::new();
+	for record in records(text, 'S') {
+		let (line, record) = record?;
+		let mut chars = record.chars();
+		let kind = chars.next();
+		let size = match kind {
+			Some('0' | '1' | '5' | '9') => 2,
+			Some('2' | '6' | '8') => 3,
+			Some('3' | '7') => 4,
+			_ => Err(Syntax(line))?,
+		};
+		let bytes = hex(chars.as_str(), line)?;
+		if bytes.len() < size + 2 || bytes[0] as usize != bytes.len() - 1 {
+			Err(Syntax(line))?;
+		}
+		if bytes.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte)) != 0xff {
+			Err(Checksum(line))?;
+		}
+		if let Some('1' | '2' | '3') = kind {
+			let data = bytes[size + 1..bytes.len() - 1].to_vec();
+			chunks.push((be(&bytes[1..=size]), data));
+		}
+	}
+	Ok(image(chunks))
+}
+
+/// Non-blank lines of `text` with their line number and without `mark`.
+fn records(text: &str, mark: char)
+-> impl Iterator<Item = Result<(usize, &str)>> {
+	text.lines().enumerate().map(|(index, record)| (index + 1, record.trim()))
+		.filter(|(_line, record)| !record.is_empty())
+		.map(move |(line, record)| record.strip_prefix(mark)
+			.map(|record| (line, record)).ok_or(Syntax(line)))
+}
+
+/// Big-endian address of `bytes`.
+fn be(bytes: &[u8]) -> usize {
+	bytes.iter().fold(0, |addr, &byte| addr << 8 | byte as usize)
+}
+
+/// Bytes of hexadecimal `digits` at `line`.
+fn hex(digits: &str, line: usize) -> Result<Vec<u8>> {
+	if !digits.len().is_multiple_of(2) || !digits.is_ascii() {
+		Err(Syntax(line))?;
+	}
+	(0..digits.len()).step_by(2)
+		.map(|index| u8::from_str_radix(&digits[index..index + 2], 16)
+			.map_err(|_| Syntax(line)))
+		.collect()
+}
+
+/// Bytes of `chunks` at their addresses filling gaps with `0xFF`.
+fn image(chunks: Vec<(usize, Vec<u8>)>) -> Vec<u8> {
+	let chunks = chunks.into_iter().filter(|(_addr, data)| !data.is_empty())
+		.collect::<Vec<_>>();
+	let first = chunks.iter().map(|(addr, _data)| *addr).min().unwrap_or(0);
+	let last = chunks.iter().map(|(addr, data)| addr + data.len()).max()
+		.unwrap_or(0);
+	let mut bytes = vec![0xff; last - first];
+	for (addr, data) in chunks {
+		bytes[addr - first..addr - first + data.len()].copy_from_slice(&data);
+	}
+	bytes
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Sample MIF of comments, ranges, and lowercase keywords.
+	const SAMPLE: &str = "\
+		-- generated\n\
+		% multi-line\n\
+		comment %\n\
+		WIDTH=16;\n\
+		depth = 8 ;\n\
+		ADDRESS_RADIX=HEX;\n\
+		DATA_RADIX=HEX;\n\
+		CONTENT BEGIN\n\
+		\t0  :   ABCD; -- first\n\
+		\t[1..7]  :   0;\n\
+		END;\n";
+
+	#[test]
+	fn tokenize_sample() {
+		let mut tokens = tokens(SAMPLE);
+		let mut next = |line| {
+			let token = tokens.next().unwrap().unwrap();
+			assert_eq!(tokens.line(), line);
+			token
+		};
+		assert_eq!(next(1), Token::Comment("generated"));
+		assert_eq!(next(2), Token::Comment("multi-line\ncomment"));
+		assert_eq!(next(4), Token::Header { key: "WIDTH", val: "16" });
+		assert_eq!(next(5), Token::Header { key: "depth", val: "8" });
+		assert_eq!(next(6), Token::Header { key: "ADDRESS_RADIX", val: "HEX" });
+		assert_eq!(next(7), Token::Header { key: "DATA_RADIX", val: "HEX" });
+		assert_eq!(next(8), Token::Begin);
+		assert_eq!(next(9), Token::Single { addr: "0", word: "ABCD" });
+		assert_eq!(next(9), Token::Comment("first"));
+		assert_eq!(next(10),
+			Token::Range { first: "1", last: "7", word: "0" });
+		assert_eq!(next(11), Token::End);
+		assert!(tokens.next().is_none());
+	}
+
+	#[test]
+	fn tokenize_invalid() {
+		for (text, line) in [("WIDTH=16", 1), ("\n% open", 2), ("\n\n0 : ;", 3),
+			("[0..] : 1;", 1), ("= 1;", 1), ("what;", 1)] {
+			let mut tokens = tokens(text);
+			assert!(matches!(tokens.next(),
+				Some(Err(Syntax(at))) if at == line));
+			assert!(tokens.next().is_none());
+		}
+	}
+
+	/// MIF of `width`, address `radix`, data `radix`, and `content`.
+	fn fixture(width: usize, radix: (&str, &str), content: &str) -> String {
+		format!("WIDTH={};\nDEPTH=4;\nADDRESS_RADIX={};\nDATA_RADIX={};\n\
+			CONTENT BEGIN\n{}END;\n", width, radix.0, radix.1, content)
+	}
+
+	#[test]
+	fn parse_declared_radixes() {
+		let words = vec![(0x00, 1), (0xa5, 1), (0xff, 1), (0x05, 1)];
+		for (radix, content) in [
+			(("HEX", "HEX"), "1 : A5;\n2 : FF;\n3 : 5;\n"),
+			(("BIN", "BIN"), "1 : 10100101;\n10 : 11111111;\n11 : 101;\n"),
+			(("OCT", "OCT"), "1 : 245;\n2 : 377;\n3 : 5;\n"),
+			(("DEC", "DEC"), "1 : -91;\n2 : -1;\n3 : 5;\n"),
+			(("UNS", "UNS"), "1 : 165;\n2 : 255;\n3 : 5;\n"),
+			(("dec", "hex"), "1 : a5;\n2 : ff;\n3 : 5;\n"),
+		] {
+			let mif = Mif::<u8>::parse(&fixture(8, radix, content)).unwrap();
+			assert_eq!(mif.words(), &words, "{:?}", radix);
+		}
+		for content in ["1 : -129;\n", "1 : 256;\n"] {
+			assert!(Mif::<u16>::parse(&fixture(8, ("DEC", "DEC"), content))
+				.is_err());
+		}
+		assert!(Mif::<u8>::parse(&fixture(8, ("UNS", "UNS"), "1 : -1;\n"))
+			.is_err());
+		assert!(Mif::<u8>::parse(&fixture(8, ("HEX", "BIN"), "1 : 2;\n"))
+			.is_err());
+		assert!(Mif::<u8>::parse(&fixture(8, ("HEX", "SEX"), "")).is_err());
+	}
+
+	#[test]
+	fn parse_huge_addresses() {
+		let max = format!("{:X}", usize::MAX);
+		for content in [
+			format!("[0..{}] : 0;\n", max),
+			format!("[{}..0] : 0;\n", max),
+			format!("[1..{}] : 0;\n", max),
+			format!("{} : 0;\n", max),
+			format!("[{}..{}] : 0;\n", max, max),
+		] {
+			assert!(Mif::<u8>::parse(&fixture(8, ("HEX", "HEX"), &content))
+				.is_err(), "{}", content);
+		}
+	}
+
+	#[test]
+	fn parse_strict_rejects_non_canonical() {
+		for (content, strict) in [
+			("0 : 1;\n[1..3] : 2;\n", None),
+			("[1..3] : 2;\n0 : 1;\n", Some(OutOfOrder(0))),
+			("0 : 1;\n[0..3] : 2;\n", Some(DuplicateAddress(0))),
+			("0 : 1;\n[1..3] : 1;\n", Some(NonCoalesced(1))),
+		] {
+			let mif = fixture(8, ("HEX", "HEX"), content);
+			assert_eq!(Mif::<u8>::parse(&mif).unwrap().depth(), 4);
+			match (Mif::<u8>::parse_strict(&mif), strict) {
+				(Ok(_), None) => {},
+				(Err(error), Some(strict)) => assert_eq!(format!("{}", error),
+					format!("{}", strict)),
+				(mif, _) => panic!("{:?} of `{}`", mif.map(|_| ()), content),
+			}
+		}
+	}
+
+	#[test]
+	fn srec_and_ihex_reject_non_ascii() {
+		assert_eq!(srec("S1030000FC\n").unwrap(), []);
+		for text in ["Sé00", "S", "S1é0", "Sx00", "S\u{1f4be}", "S40300FC"] {
+			assert!(matches!(srec(text), Err(Syntax(1))), "{:?}", text);
+		}
+		assert!(matches!(ihex(":é0"), Err(Syntax(1))));
+	}
+
+	#[test]
+	fn without_comments_equals_naive_filter() {
+		let mut text = String::from("\
+			-- generated\n\
+			% multi-line\n\
+			comment with -- and ; %\n\
+			WIDTH=16; -- width\n\
+			% depth % DEPTH=64;\n\
+			ADDRESS_RADIX=HEX; DATA_RADIX=HEX; -- 50% done\n\
+			CONTENT BEGIN -- content\n");
+		for addr in 0..32 {
+			text += &format!("-- word {0} at [{0}..{0}] : {0};\n\
+				\t{0:X} : {1:X}; % inline : {1}; %\n", addr, addr % 3);
+		}
+		text += "\t[20..3F] : FFFF; % multi-line\nrun %\n-- last\nEND;\n\
+			-- done\n";
+		fn with_lines(mut tokens: Tokens) -> Vec<(usize, Token)> {
+			let mut lines = Vec::new();
+			while let Some(token) = tokens.next() {
+				lines.push((tokens.line(), token.unwrap()));
+			}
+			lines
+		}
+		let naive = with_lines(tokens(&text)).into_iter()
+			.filter(|(_line, token)| !matches!(token, Token::Comment(_)))
+			.collect::<Vec<_>>();
+		assert_eq!(with_lines(tokens(&text).without_comments()), naive);
+		let stripped = naive.iter().map(|(_line, token)| match *token {
+			Token::Header { key, val } => format!("{}={};\n", key, val),
+			Token::Single { addr, word } => format!("{} : {};\n", addr, word),
+			Token::Range { first, last, word } =>
+				format!("[{}..{}] : {};\n", first, last, word),
+			Token::Begin => "CONTENT BEGIN\n".into(),
+			Token::End => "END;\n".into(),
+			Token::Comment(_) => unreachable!(),
+		}).collect::<String>();
+		let mif = Mif::<u16>::parse(&text).unwrap();
+		assert_eq!(mif, Mif::<u16>::parse(&stripped).unwrap());
+		assert_eq!((mif.depth(), mif.run_count()), (64, 33));
+	}
+}
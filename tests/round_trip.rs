@@ -0,0 +1,57 @@
+use proptest::prelude::*;
+use mif::{Mif, First, Style};
+
+/// Words of `width` in runs of bulks up to `bulk`.
+fn runs(width: usize, bulk: usize)
+-> impl Strategy<Value = Vec<(u128, usize)>> {
+	let max_value = u128::MAX >> (128 - width);
+	prop::collection::vec((0..=max_value, 1..=bulk), 0..32)
+}
+
+/// Bytes of `depth` LSB/MSB-`first` words of `width` with excess bits cleared.
+fn bytes(width: usize, first: First) -> impl Strategy<Value = Vec<u8>> {
+	let align = Mif::<u128>::new(width).unwrap().align();
+	let mask = (0xff_u16 >> (align * 8 - width)) as u8;
+	let index = if first == First::Lsb { align - 1 } else { 0 };
+	prop::collection::vec(any::<u8>(), 0..32 * align).prop_map(move |bytes| {
+		let mut bytes = bytes[..bytes.len() / align * align].to_vec();
+		bytes.chunks_mut(align).for_each(|word| word[index] &= mask);
+		bytes
+	})
+}
+
+/// LSB or MSB first.
+fn first() -> impl Strategy<Value = First> {
+	prop_oneof![Just(First::Lsb), Just(First::Msb)]
+}
+
+proptest! {
+	#[test]
+	fn write_then_parse(
+		(width, runs) in (1..=128_usize).prop_flat_map(|width|
+			(Just(width), runs(width, 16))),
+		single in any::<bool>(),
+	) {
+		let mut mif = Mif::<u128>::new(width).unwrap();
+		for (word, bulk) in runs {
+			mif.push(word, bulk).unwrap();
+		}
+		let style = Style { single, ..Style::default() };
+		let text = mif.to_string_mif(&style).unwrap();
+		prop_assert_eq!(Mif::<u128>::parse(&text).unwrap(), mif);
+	}
+
+	#[test]
+	fn read_then_write_bytes(
+		(width, first, bytes) in (1..=128_usize, first()).prop_flat_map(
+			|(width, first)| (Just(width), Just(first), bytes(width, first))),
+	) {
+		let mut mif = Mif::<u128>::new(width).unwrap();
+		let depth = bytes.len() / mif.align();
+		mif.read(&mut &bytes[..], depth, first).unwrap();
+		prop_assert_eq!(mif.depth(), depth);
+		let mut written = Vec::new();
+		mif.write_bytes(&mut written, first).unwrap();
+		prop_assert_eq!(written, bytes);
+	}
+}